@@ -1,43 +1,212 @@
 use crate::{
     error::ContractError,
-    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
-    state::{REMAINING_USDSIM, WTOEKN_TOTAL_BALANCE, WTOKEN_CONTRACT},
+    msg::{AccessControlQueryMsg, ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{
+        Config, StakingAccess, ALLOWLIST, CONFIG, FORFEITED_USDSIM, RECEIPT_TOKEN,
+        REMAINING_USDSIM, WTOEKN_TOTAL_BALANCE, WTOKEN_CONTRACT,
+    },
 };
 
+use cw2::set_contract_version;
+
 use cosmwasm_std::{
-    to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage,
+    to_json_binary, Addr, Api, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Reply, Response,
+    StdResult, Storage, SubMsg, WasmMsg,
 };
 
+const CONTRACT_NAME: &str = "crates.io:reward-contract";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 const USDSIM_DENOM: &str = "usdsim";
 
+const RECEIPT_TOKEN_NAME: &str = "Staked Wtoken";
+const RECEIPT_TOKEN_SYMBOL: &str = "sWTK";
+const RECEIPT_TOKEN_DECIMALS: u8 = 6;
+
+const INSTANTIATE_RECEIPT_TOKEN_REPLY_ID: u64 = 1;
+
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
+    env: Env,
+    info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
-    let InstantiateMsg { wtoken_contract } = msg;
+) -> Result<Response, ContractError> {
+    let InstantiateMsg {
+        wtoken_contract,
+        receipt_token_code_id,
+        max_stake_per_user,
+        max_total_stake,
+        staking_access,
+        reward_vesting_period,
+        protocol_fee,
+        referral_share,
+        fee_collector,
+    } = msg;
     let store = deps.storage;
     let wtoken_addr = deps.api.addr_validate(&wtoken_contract)?;
+    let staking_access = staking_access.unwrap_or(StakingAccess::Open);
+    let fee_collector = match fee_collector {
+        Some(fee_collector) => deps.api.addr_validate(&fee_collector)?,
+        None => info.sender.clone(),
+    };
+    validate_fees(protocol_fee, referral_share)?;
+    let config = Config {
+        admin: info.sender,
+        max_stake_per_user,
+        max_total_stake,
+        staking_access: validate_staking_access(deps.api, staking_access)?,
+        reward_vesting_period,
+        protocol_fee,
+        referral_share,
+        fee_collector,
+        epoch_length: 0,
+        funders: None,
+        paused: false,
+    };
 
     WTOKEN_CONTRACT.save(store, &wtoken_addr)?;
-    WTOEKN_TOTAL_BALANCE.save(store, &0u128.into())?;
+    CONFIG.save(store, &config)?;
+    WTOEKN_TOTAL_BALANCE.save(store, &0u128.into(), env.block.height)?;
     REMAINING_USDSIM.save(store, &0u128.into())?;
+    FORFEITED_USDSIM.save(store, &0u128.into())?;
+    set_contract_version(store, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // The receipt token address is stored once it is known, in `reply`
+    let receipt_init_msg = swtoken::msg::InstantiateMsg {
+        name: RECEIPT_TOKEN_NAME.into(),
+        symbol: RECEIPT_TOKEN_SYMBOL.into(),
+        decimals: RECEIPT_TOKEN_DECIMALS,
+    };
+    let instantiate_receipt_token = WasmMsg::Instantiate {
+        admin: None,
+        code_id: receipt_token_code_id,
+        msg: to_json_binary(&receipt_init_msg)?,
+        funds: vec![],
+        label: RECEIPT_TOKEN_SYMBOL.into(),
+    };
+
+    Ok(Response::new().add_submessage(SubMsg::reply_on_success(
+        instantiate_receipt_token,
+        INSTANTIATE_RECEIPT_TOKEN_REPLY_ID,
+    )))
+}
+
+fn validate_staking_access(api: &dyn Api, access: StakingAccess) -> StdResult<StakingAccess> {
+    Ok(match access {
+        StakingAccess::External { contract } => StakingAccess::External {
+            contract: api.addr_validate(contract.as_str())?,
+        },
+        access => access,
+    })
+}
+
+fn validate_fees(protocol_fee: Decimal, referral_share: Decimal) -> Result<(), ContractError> {
+    if protocol_fee > Decimal::one() || referral_share > protocol_fee {
+        Err(ContractError::InvalidFees)
+    } else {
+        Ok(())
+    }
+}
+
+fn can_stake(deps: Deps, address: &Addr) -> StdResult<bool> {
+    let config = CONFIG.load(deps.storage)?;
+
+    match config.staking_access {
+        StakingAccess::Open => Ok(true),
+        StakingAccess::Allowlist => Ok(ALLOWLIST.has(deps.storage, address.clone())),
+        StakingAccess::External { contract } => deps.querier.query_wasm_smart(
+            contract,
+            &AccessControlQueryMsg::IsAllowed {
+                address: address.into(),
+            },
+        ),
+    }
+}
+
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_RECEIPT_TOKEN_REPLY_ID => reply::instantiate_receipt_token(deps, msg),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+mod reply {
+    use cosmwasm_std::StdError;
+    use cw_utils::parse_instantiate_response_data;
+
+    use super::*;
+
+    pub fn instantiate_receipt_token(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+        let res = msg.result.into_result().map_err(StdError::generic_err)?;
+
+        // Chains running CosmWasm 1.x only fill the deprecated `data` field
+        #[allow(deprecated)]
+        let data = res
+            .msg_responses
+            .into_iter()
+            .next()
+            .map(|msg_response| msg_response.value)
+            .or(res.data)
+            .ok_or(ContractError::InvalidReceiptTokenReply)?;
+
+        let instantiate_response = parse_instantiate_response_data(&data)
+            .map_err(|_| ContractError::InvalidReceiptTokenReply)?;
+        let receipt_token = deps
+            .api
+            .addr_validate(&instantiate_response.contract_address)?;
 
-    Ok(Response::new())
+        RECEIPT_TOKEN.save(deps.storage, &receipt_token)?;
+
+        Ok(Response::new().add_attribute("receipt_token", receipt_token))
+    }
 }
 
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     use QueryMsg::*;
     Ok(match msg {
         WtokenBalance { address } => to_json_binary(&query::wtoken_balance(deps, address)?)?,
         WtokenTotalBalance {} => to_json_binary(&query::wtoken_total_balance(deps)?)?,
         RemainingUsdsim {} => to_json_binary(&query::remaining_usdsim(deps)?)?,
+        ReceiptToken {} => to_json_binary(&query::receipt_token(deps)?)?,
+        Config {} => to_json_binary(&query::config(deps)?)?,
+        CanStake { address } => to_json_binary(&query::can_stake(deps, address)?)?,
+        Rewards { address } => to_json_binary(&query::rewards(deps, env, address)?)?,
+        ForfeitedUsdsim {} => to_json_binary(&query::forfeited_usdsim(deps)?)?,
+        Referrals {
+            referrer,
+            start_after,
+            limit,
+        } => to_json_binary(&query::referrals(deps, referrer, start_after, limit)?)?,
+        ReferralEarnings { address } => to_json_binary(&query::referral_earnings(deps, address)?)?,
+        VotingPowerAtHeight { address, height } => {
+            to_json_binary(&query::voting_power_at_height(deps, env, address, height)?)?
+        }
+        TotalPowerAtHeight { height } => {
+            to_json_binary(&query::total_power_at_height(deps, env, height)?)?
+        }
+        Info {} => to_json_binary(&query::info(deps)?)?,
+        Dao {} => to_json_binary(&query::config(deps)?.admin)?,
     })
 }
 
 mod query {
-    use crate::state::{UsdsimBalance, WtokenBalance, WTOKEN_BALANCES};
+    use cosmwasm_std::{Order, Uint128};
+    use cw_storage_plus::Bound;
+
+    use cw2::get_contract_version;
+
+    use crate::{
+        msg::{
+            InfoResponse, RewardsResponse, TotalPowerAtHeightResponse, VotingPowerAtHeightResponse,
+        },
+        state::{
+            UsdsimBalance, WtokenBalance, REFERRALS, REFERRAL_EARNINGS, USDSIM_REWARDS,
+            WTOKEN_BALANCES,
+        },
+    };
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
 
     use super::*;
 
@@ -53,43 +222,190 @@ mod query {
     pub fn remaining_usdsim(deps: Deps) -> StdResult<UsdsimBalance> {
         REMAINING_USDSIM.load(deps.storage)
     }
+
+    pub fn receipt_token(deps: Deps) -> StdResult<Addr> {
+        RECEIPT_TOKEN.load(deps.storage)
+    }
+
+    pub fn config(deps: Deps) -> StdResult<Config> {
+        CONFIG.load(deps.storage)
+    }
+
+    pub fn can_stake(deps: Deps, address: String) -> StdResult<bool> {
+        let addr = deps.api.addr_validate(&address)?;
+        super::can_stake(deps, &addr)
+    }
+
+    pub fn rewards(deps: Deps, env: Env, address: String) -> StdResult<RewardsResponse> {
+        let addr = deps.api.addr_validate(&address)?;
+        let mut rewards = USDSIM_REWARDS
+            .may_load(deps.storage, addr)?
+            .unwrap_or_default();
+        rewards.settle(env.block.time);
+
+        Ok(RewardsResponse {
+            vested: rewards.vested,
+            unvested: rewards.unvested(),
+        })
+    }
+
+    pub fn forfeited_usdsim(deps: Deps) -> StdResult<UsdsimBalance> {
+        FORFEITED_USDSIM.load(deps.storage)
+    }
+
+    pub fn referrals(
+        deps: Deps,
+        referrer: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Addr>> {
+        let referrer = deps.api.addr_validate(&referrer)?;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        REFERRALS
+            .prefix(referrer)
+            .keys(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect()
+    }
+
+    pub fn referral_earnings(deps: Deps, address: String) -> StdResult<UsdsimBalance> {
+        let addr = deps.api.addr_validate(&address)?;
+        Ok(REFERRAL_EARNINGS
+            .may_load(deps.storage, addr)?
+            .unwrap_or(UsdsimBalance(Uint128::zero())))
+    }
+
+    pub fn voting_power_at_height(
+        deps: Deps,
+        env: Env,
+        address: String,
+        height: Option<u64>,
+    ) -> StdResult<VotingPowerAtHeightResponse> {
+        let addr = deps.api.addr_validate(&address)?;
+        let height = height.unwrap_or(env.block.height);
+        let power = WTOKEN_BALANCES
+            .may_load_at_height(deps.storage, addr, height)?
+            .map(|WtokenBalance(staked)| staked)
+            .unwrap_or_default();
+        Ok(VotingPowerAtHeightResponse { power, height })
+    }
+
+    pub fn total_power_at_height(
+        deps: Deps,
+        env: Env,
+        height: Option<u64>,
+    ) -> StdResult<TotalPowerAtHeightResponse> {
+        let height = height.unwrap_or(env.block.height);
+        let power = WTOEKN_TOTAL_BALANCE
+            .may_load_at_height(deps.storage, height)?
+            .map(|WtokenBalance(staked)| staked)
+            .unwrap_or_default();
+        Ok(TotalPowerAtHeightResponse { power, height })
+    }
+
+    pub fn info(deps: Deps) -> StdResult<InfoResponse> {
+        Ok(InfoResponse {
+            info: get_contract_version(deps.storage)?,
+        })
+    }
 }
 
 #[allow(dead_code)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     use ExecuteMsg::*;
 
     match msg {
-        Receive(receive_msg) => exec::receive_wtoken(deps, info, receive_msg),
-        ReceiveUsdsim {} => exec::receive_usdsim(deps, info),
+        Receive(receive_msg) => exec::receive(deps, env, info, receive_msg),
+        ReceiveUsdsim {} => exec::receive_usdsim(deps, env, info),
+        ClaimRewards {} => exec::claim_rewards(deps, env, info),
+        ReceiptTransferHook(hook_msg) => exec::receipt_transfer_hook(deps, env, info, hook_msg),
+        UpdateStakeCaps {
+            max_stake_per_user,
+            max_total_stake,
+        } => exec::update_stake_caps(deps, info, max_stake_per_user, max_total_stake),
+        UpdateStakingAccess { staking_access } => {
+            exec::update_staking_access(deps, info, staking_access)
+        }
+        UpdateAllowlist { add, remove } => exec::update_allowlist(deps, info, add, remove),
+        UpdateRewardVestingPeriod {
+            reward_vesting_period,
+        } => exec::update_reward_vesting_period(deps, info, reward_vesting_period),
+        UpdateFees {
+            protocol_fee,
+            referral_share,
+            fee_collector,
+        } => exec::update_fees(deps, info, protocol_fee, referral_share, fee_collector),
+        UpdateAdmin { admin } => exec::update_admin(deps, info, admin),
+        UpdateEpochLength { epoch_length } => exec::update_epoch_length(deps, info, epoch_length),
+        UpdateFunders { funders } => exec::update_funders(deps, info, funders),
+        Pause {} => exec::update_paused(deps, info, true),
+        Unpause {} => exec::update_paused(deps, info, false),
     }
 }
 
 mod exec {
-    use cosmwasm_std::{coins, BankMsg, Order, Uint128};
-    use cw20::Cw20ReceiveMsg;
+    use cosmwasm_std::{coins, from_json, BankMsg, Empty, Order, Timestamp, Uint128};
+    use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+    use swtoken::msg::ReceiptTransferHookMsg;
 
-    use crate::state::{
-        UsdsimBalance, WtokenBalance, REMAINING_USDSIM, WTOEKN_TOTAL_BALANCE, WTOKEN_BALANCES,
+    use crate::{
+        msg::ReceiveMsg,
+        state::{
+            UsdsimBalance, WtokenBalance, LAST_DISTRIBUTION, REFERRALS, REFERRAL_EARNINGS,
+            REFERRERS, REMAINING_USDSIM, USDSIM_REWARDS, WTOEKN_TOTAL_BALANCE, WTOKEN_BALANCES,
+        },
     };
 
     use super::*;
 
-    pub fn receive_wtoken(
+    pub fn receive(
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
         msg: Cw20ReceiveMsg,
     ) -> Result<Response, ContractError> {
         let wtoken_contract = WTOKEN_CONTRACT.load(deps.storage)?;
+        let receipt_token = RECEIPT_TOKEN.load(deps.storage)?;
         let sender = deps.api.addr_validate(&msg.sender)?;
 
         if info.sender == wtoken_contract {
-            receive_wtoken_inner(deps.storage, sender, msg.amount)
+            let ReceiveMsg::Stake { referrer } = if msg.msg.is_empty() {
+                ReceiveMsg::Stake { referrer: None }
+            } else {
+                from_json(&msg.msg)?
+            };
+
+            if CONFIG.load(deps.storage)?.paused {
+                return Err(ContractError::Paused);
+            }
+            ensure_can_stake(deps.as_ref(), &sender)?;
+            if let Some(referrer) = referrer {
+                let referrer = deps.api.addr_validate(&referrer)?;
+                record_referral(deps.storage, &sender, referrer)?;
+            }
+            receive_wtoken_inner(deps.storage, env.block.height, sender, msg.amount)
+        } else if info.sender == receipt_token {
+            receive_receipt_inner(
+                deps.storage,
+                env.block.height,
+                env.contract.address,
+                sender,
+                msg.amount,
+            )
         } else {
             Err(ContractError::InvalidWtokenAddress {
                 address: info.sender,
@@ -99,6 +415,7 @@ mod exec {
 
     fn receive_wtoken_inner(
         storage: &mut dyn Storage,
+        height: u64,
         sender: Addr,
         amount: Uint128,
     ) -> Result<Response, ContractError> {
@@ -106,276 +423,1924 @@ mod exec {
             return Err(ContractError::ZeroAmountReceived);
         }
 
-        WTOKEN_BALANCES.update(storage, sender, |may_balance| {
-            let new_amount = match may_balance {
-                None => amount,
-                Some(WtokenBalance(prev_amount)) => amount + prev_amount,
-            };
-            Result::<_, ContractError>::Ok(WtokenBalance(new_amount))
-        })?;
+        let accepted_amount = accepted_stake(storage, &sender, amount)?;
+        let refund_amount = amount - accepted_amount;
+
+        increase_balance(storage, height, sender.clone(), accepted_amount)?;
+
+        let WtokenBalance(prev_amount) = WTOEKN_TOTAL_BALANCE.load(storage)?;
+        WTOEKN_TOTAL_BALANCE.save(
+            storage,
+            &WtokenBalance(prev_amount + accepted_amount),
+            height,
+        )?;
+
+        let mint_receipt = WasmMsg::Execute {
+            contract_addr: RECEIPT_TOKEN.load(storage)?.into(),
+            msg: to_json_binary(&swtoken::msg::ExecuteMsg::Mint {
+                recipient: sender.to_string(),
+                amount: accepted_amount,
+            })?,
+            funds: vec![],
+        };
+        let resp = Response::new().add_message(mint_receipt);
 
-        WTOEKN_TOTAL_BALANCE.update(storage, |WtokenBalance(prev_amount)| {
-            Result::<_, ContractError>::Ok(WtokenBalance(prev_amount + amount))
-        })?;
+        if refund_amount.is_zero() {
+            return Ok(resp);
+        }
 
-        Ok(Response::new())
+        let refund = WasmMsg::Execute {
+            contract_addr: WTOKEN_CONTRACT.load(storage)?.into(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: sender.into(),
+                amount: refund_amount,
+            })?,
+            funds: vec![],
+        };
+
+        Ok(resp
+            .add_message(refund)
+            .add_attribute("refunded", refund_amount))
     }
 
-    // We assume receiving USDsim every 24 hours
-    pub fn receive_usdsim(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
-        let sent_usdsim = info.funds.iter().find(|coin| coin.denom == USDSIM_DENOM);
+    // Caps only apply to deposits, receipt token transfers are not limited
+    fn accepted_stake(
+        storage: &dyn Storage,
+        sender: &Addr,
+        amount: Uint128,
+    ) -> Result<Uint128, ContractError> {
+        let config = CONFIG.load(storage)?;
+        let mut accepted_amount = amount;
+
+        if let Some(cap) = config.max_stake_per_user {
+            let staked = WTOKEN_BALANCES
+                .may_load(storage, sender.clone())?
+                .map(|WtokenBalance(staked)| staked)
+                .unwrap_or_default();
+            let room = cap.saturating_sub(staked);
+            if room.is_zero() {
+                return Err(ContractError::UserStakeCapReached { cap });
+            }
+            accepted_amount = accepted_amount.min(room);
+        }
 
-        if let Some(coin) = sent_usdsim {
-            receive_usdsim_inner(deps.storage, coin.amount)
-        } else {
-            Err(ContractError::NoUsdsimTokensSent)
+        if let Some(cap) = config.max_total_stake {
+            let WtokenBalance(total_staked) = WTOEKN_TOTAL_BALANCE.load(storage)?;
+            let room = cap.saturating_sub(total_staked);
+            if room.is_zero() {
+                return Err(ContractError::TotalStakeCapReached { cap });
+            }
+            accepted_amount = accepted_amount.min(room);
         }
+
+        Ok(accepted_amount)
     }
 
-    fn receive_usdsim_inner(
+    // Receipt tokens sent back are redeemed for the same amount of Wtoken. The
+    // transfer hook has already moved the stake onto this contract's own entry.
+    fn receive_receipt_inner(
         storage: &mut dyn Storage,
+        height: u64,
+        contract_addr: Addr,
+        sender: Addr,
         amount: Uint128,
     ) -> Result<Response, ContractError> {
         if amount == Uint128::zero() {
-            return Ok(Response::new());
+            return Err(ContractError::ZeroAmountReceived);
         }
 
-        let total_balance = WTOEKN_TOTAL_BALANCE.load(storage)?;
-        let remaining_usdsim = REMAINING_USDSIM.load(storage)?;
+        decrease_balance(storage, height, contract_addr, amount)?;
 
-        let (resp, remaining_amount) = WTOKEN_BALANCES
-            .range(storage, None, None, Order::Ascending)
-            .try_fold(
-                (Response::new(), amount + remaining_usdsim.0),
-                |(resp, remaining_amount), item| -> Result<_, ContractError> {
-                    let (recipient_addr, balance) = item?;
-                    let reward_amount = balance.0.checked_mul(amount)? / total_balance.0;
-                    let resp = resp.add_message(BankMsg::Send {
-                        to_address: recipient_addr.into(),
-                        amount: coins(reward_amount.into(), USDSIM_DENOM),
-                    });
-                    Ok((resp, remaining_amount - reward_amount))
-                },
-            )?;
+        let WtokenBalance(prev_amount) = WTOEKN_TOTAL_BALANCE.load(storage)?;
+        WTOEKN_TOTAL_BALANCE.save(
+            storage,
+            &WtokenBalance(prev_amount.checked_sub(amount)?),
+            height,
+        )?;
 
-        REMAINING_USDSIM.save(storage, &UsdsimBalance(remaining_amount))?;
+        let burn_receipt = WasmMsg::Execute {
+            contract_addr: RECEIPT_TOKEN.load(storage)?.into(),
+            msg: to_json_binary(&swtoken::msg::ExecuteMsg::Burn { amount })?,
+            funds: vec![],
+        };
+        let return_wtoken = WasmMsg::Execute {
+            contract_addr: WTOKEN_CONTRACT.load(storage)?.into(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: sender.into(),
+                amount,
+            })?,
+            funds: vec![],
+        };
 
-        Ok(resp)
+        Ok(Response::new()
+            .add_message(burn_receipt)
+            .add_message(return_wtoken))
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use cosmwasm_std::{
-        coins, from_json,
-        testing::{mock_dependencies_with_balance, mock_env, mock_info, MockApi},
-        Addr,
-    };
-    use cw20::Cw20ReceiveMsg;
-    use lazy_static::lazy_static;
-    use serde::de::DeserializeOwned;
 
-    use crate::{
-        execute, query,
-        state::{UsdsimBalance, WtokenBalance},
-    };
+    // Only the first stake of an address can carry a referrer
+    fn record_referral(
+        storage: &mut dyn Storage,
+        referee: &Addr,
+        referrer: Addr,
+    ) -> Result<(), ContractError> {
+        if referrer == referee {
+            return Err(ContractError::SelfReferral);
+        }
+        if REFERRERS.has(storage, referee.clone())
+            || WTOKEN_BALANCES
+                .may_load(storage, referee.clone())?
+                .is_some()
+        {
+            return Ok(());
+        }
 
-    use super::*;
+        REFERRERS.save(storage, referee.clone(), &referrer)?;
+        REFERRALS.save(storage, (referrer, referee.clone()), &Empty {})?;
 
-    pub fn query2<T>(deps: Deps, msg: QueryMsg) -> StdResult<T>
-    where
-        T: DeserializeOwned,
-    {
-        let env = mock_env();
-        let data = query(deps, env, msg)?;
-        from_json(data)
+        Ok(())
     }
 
-    lazy_static! {
-        static ref WTOKEN_ADDRESS: String = {
-            let mock_api = MockApi::default();
-            mock_api.addr_make("wtoken-address").to_string()
-        };
-    }
+    pub fn receipt_transfer_hook(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: ReceiptTransferHookMsg,
+    ) -> Result<Response, ContractError> {
+        let receipt_token = RECEIPT_TOKEN.load(deps.storage)?;
+        if info.sender != receipt_token {
+            return Err(ContractError::InvalidReceiptTokenAddress {
+                address: info.sender,
+            });
+        }
 
-    fn do_instantiation(mut deps: DepsMut) {
-        let instantiate_msg = InstantiateMsg {
-            wtoken_contract: WTOKEN_ADDRESS.clone(),
-        };
-        let info = mock_info("creator", &[]);
-        let env = mock_env();
-        let res = instantiate(deps.branch(), env, info, instantiate_msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        if msg.amount == Uint128::zero() {
+            return Ok(Response::new());
+        }
+
+        let from = deps.api.addr_validate(&msg.from)?;
+        let to = deps.api.addr_validate(&msg.to)?;
+
+        // Receiving a staked position counts as staking, except when it is being redeemed
+        if to != env.contract.address {
+            ensure_can_stake(deps.as_ref(), &to)?;
+        }
+
+        forfeit_unvested_rewards(deps.storage, env.block.time, &from, msg.amount)?;
+        decrease_balance(deps.storage, env.block.height, from, msg.amount)?;
+        increase_balance(deps.storage, env.block.height, to, msg.amount)?;
+
+        Ok(Response::new())
     }
 
-    fn execute_receive_wtoken(
+    pub fn update_stake_caps(
         deps: DepsMut,
-        sender: &str,
-        amount: u128,
+        info: MessageInfo,
+        max_stake_per_user: Option<Uint128>,
+        max_total_stake: Option<Uint128>,
     ) -> Result<Response, ContractError> {
-        let env = mock_env();
-        let info = mock_info(&WTOKEN_ADDRESS, &[]);
-        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: sender.into(),
-            amount: amount.into(),
-            msg: Binary::default(),
-        });
-        execute(deps, env.clone(), info, msg)
+        let mut config = CONFIG.load(deps.storage)?;
+        if info.sender != config.admin {
+            return Err(ContractError::Unauthorized);
+        }
+
+        config.max_stake_per_user = max_stake_per_user;
+        config.max_total_stake = max_total_stake;
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new())
     }
 
-    fn execute_receive_usdsim(
+    pub fn update_staking_access(
         deps: DepsMut,
-        sender: &str,
-        amount: u128,
+        info: MessageInfo,
+        staking_access: StakingAccess,
     ) -> Result<Response, ContractError> {
-        let env = mock_env();
-        let info = mock_info(sender, &coins(amount, USDSIM_DENOM));
-        let msg = ExecuteMsg::ReceiveUsdsim {};
-        execute(deps, env.clone(), info, msg)
+        let mut config = CONFIG.load(deps.storage)?;
+        if info.sender != config.admin {
+            return Err(ContractError::Unauthorized);
+        }
+
+        config.staking_access = validate_staking_access(deps.api, staking_access)?;
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new())
     }
 
-    #[test]
-    fn instantiation() {
-        let mut deps = mock_dependencies_with_balance(&[]);
+    pub fn update_allowlist(
+        deps: DepsMut,
+        info: MessageInfo,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if info.sender != config.admin {
+            return Err(ContractError::Unauthorized);
+        }
 
-        let instantiate_msg = InstantiateMsg {
-            wtoken_contract: WTOKEN_ADDRESS.clone(),
-        };
-        let info = mock_info("creator", &[]);
-        let env = mock_env();
-        let res = instantiate(deps.as_mut(), env, info, instantiate_msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        for address in add {
+            let addr = deps.api.addr_validate(&address)?;
+            ALLOWLIST.save(deps.storage, addr, &Empty {})?;
+        }
+        for address in remove {
+            let addr = deps.api.addr_validate(&address)?;
+            ALLOWLIST.remove(deps.storage, addr);
+        }
 
-        let msg = QueryMsg::WtokenTotalBalance {};
-        let total_balance: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
-        assert_eq!(total_balance, WtokenBalance::from(0u128));
+        Ok(Response::new())
+    }
 
-        let msg = QueryMsg::RemainingUsdsim {};
-        let remaining_usdsim: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
-        assert_eq!(remaining_usdsim, WtokenBalance::from(0u128));
+    fn ensure_can_stake(deps: Deps, address: &Addr) -> Result<(), ContractError> {
+        if can_stake(deps, address)? {
+            Ok(())
+        } else {
+            Err(ContractError::NotAllowlisted {
+                address: address.clone(),
+            })
+        }
     }
 
-    #[test]
-    fn exec_receive_wtoken() {
-        let mut deps = mock_dependencies_with_balance(&[]);
-        do_instantiation(deps.as_mut());
+    pub fn update_reward_vesting_period(
+        deps: DepsMut,
+        info: MessageInfo,
+        reward_vesting_period: u64,
+    ) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        if info.sender != config.admin {
+            return Err(ContractError::Unauthorized);
+        }
 
-        let sender = deps.api.addr_make("user0001").to_string();
-        let sender2 = deps.api.addr_make("user0002").to_string();
+        config.reward_vesting_period = reward_vesting_period;
+        CONFIG.save(deps.storage, &config)?;
 
-        // --- execution 1 ---
+        Ok(Response::new())
+    }
 
-        execute_receive_wtoken(deps.as_mut(), &sender, 100u128).unwrap();
+    pub fn update_fees(
+        deps: DepsMut,
+        info: MessageInfo,
+        protocol_fee: Decimal,
+        referral_share: Decimal,
+        fee_collector: String,
+    ) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        if info.sender != config.admin {
+            return Err(ContractError::Unauthorized);
+        }
 
-        let msg = QueryMsg::WtokenBalance {
-            address: sender.clone(),
-        };
-        let balance_query_resp: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
+        validate_fees(protocol_fee, referral_share)?;
+        config.protocol_fee = protocol_fee;
+        config.referral_share = referral_share;
+        config.fee_collector = deps.api.addr_validate(&fee_collector)?;
+        CONFIG.save(deps.storage, &config)?;
 
-        let msg = QueryMsg::WtokenTotalBalance {};
-        let total_balance_query_resp: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
+        Ok(Response::new())
+    }
 
-        assert_eq!(balance_query_resp, WtokenBalance::from(100u128));
-        assert_eq!(total_balance_query_resp, WtokenBalance::from(100u128));
+    pub fn update_admin(
+        deps: DepsMut,
+        info: MessageInfo,
+        admin: String,
+    ) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        if info.sender != config.admin {
+            return Err(ContractError::Unauthorized);
+        }
 
-        // --- execution 2 ---
+        config.admin = deps.api.addr_validate(&admin)?;
+        CONFIG.save(deps.storage, &config)?;
 
-        execute_receive_wtoken(deps.as_mut(), &sender2, 20u128).unwrap();
+        Ok(Response::new().add_attribute("admin", config.admin))
+    }
 
-        let msg = QueryMsg::WtokenBalance {
-            address: sender2.clone(),
-        };
-        let balance_query_resp2: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
+    pub fn update_epoch_length(
+        deps: DepsMut,
+        info: MessageInfo,
+        epoch_length: u64,
+    ) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        if info.sender != config.admin {
+            return Err(ContractError::Unauthorized);
+        }
 
-        let msg = QueryMsg::WtokenTotalBalance {};
-        let total_balance_query_resp2: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
+        config.epoch_length = epoch_length;
+        validate_epoch(&config)?;
+        CONFIG.save(deps.storage, &config)?;
 
-        assert_eq!(balance_query_resp2, WtokenBalance::from(20u128));
-        assert_eq!(total_balance_query_resp2, WtokenBalance::from(120u128));
+        Ok(Response::new().add_attribute("epoch_length", epoch_length.to_string()))
+    }
 
-        // --- execution 3 ---
+    pub fn update_funders(
+        deps: DepsMut,
+        info: MessageInfo,
+        funders: Option<Vec<String>>,
+    ) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        if info.sender != config.admin {
+            return Err(ContractError::Unauthorized);
+        }
 
-        execute_receive_wtoken(deps.as_mut(), &sender, 50u128).unwrap();
+        config.funders = funders
+            .map(|funders| {
+                funders
+                    .iter()
+                    .map(|funder| deps.api.addr_validate(funder))
+                    .collect::<StdResult<_>>()
+            })
+            .transpose()?;
+        validate_epoch(&config)?;
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new())
+    }
+
+    // A distribution by anyone would lock the funders out for the epoch
+    fn validate_epoch(config: &Config) -> Result<(), ContractError> {
+        if config.epoch_length > 0 && config.funders.is_none() {
+            return Err(ContractError::EpochWithoutFunders);
+        }
+        Ok(())
+    }
+
+    pub fn update_paused(
+        deps: DepsMut,
+        info: MessageInfo,
+        paused: bool,
+    ) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        if info.sender != config.admin {
+            return Err(ContractError::Unauthorized);
+        }
+
+        config.paused = paused;
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new().add_attribute("paused", paused.to_string()))
+    }
+
+    pub fn claim_rewards(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let mut rewards = USDSIM_REWARDS
+            .may_load(deps.storage, info.sender.clone())?
+            .ok_or(ContractError::NoRewardsToClaim)?;
+        rewards.settle(env.block.time);
+
+        let claimed = rewards.vested;
+        if claimed.is_zero() {
+            return Err(ContractError::NoRewardsToClaim);
+        }
+
+        rewards.vested = Uint128::zero();
+        if rewards.tranches.is_empty() {
+            USDSIM_REWARDS.remove(deps.storage, info.sender.clone());
+        } else {
+            USDSIM_REWARDS.save(deps.storage, info.sender.clone(), &rewards)?;
+        }
+
+        Ok(Response::new().add_message(BankMsg::Send {
+            to_address: info.sender.into(),
+            amount: coins(claimed.into(), USDSIM_DENOM),
+        }))
+    }
+
+    // Moving stake away, by unstaking or transferring receipt tokens, gives up the
+    // same share of the unvested rewards
+    fn forfeit_unvested_rewards(
+        storage: &mut dyn Storage,
+        now: Timestamp,
+        addr: &Addr,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        let Some(mut rewards) = USDSIM_REWARDS.may_load(storage, addr.clone())? else {
+            return Ok(());
+        };
+        let Some(WtokenBalance(staked)) = WTOKEN_BALANCES.may_load(storage, addr.clone())? else {
+            return Ok(());
+        };
+
+        rewards.settle(now);
+        let forfeited = rewards.forfeit(amount.min(staked), staked);
+        if forfeited.is_zero() {
+            return Ok(());
+        }
+
+        USDSIM_REWARDS.save(storage, addr.clone(), &rewards)?;
+        FORFEITED_USDSIM.update(storage, |UsdsimBalance(prev_amount)| {
+            Result::<_, ContractError>::Ok(UsdsimBalance(prev_amount + forfeited))
+        })?;
+
+        Ok(())
+    }
+
+    fn increase_balance(
+        storage: &mut dyn Storage,
+        height: u64,
+        addr: Addr,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        WTOKEN_BALANCES.update(storage, addr, height, |may_balance| {
+            let new_amount = match may_balance {
+                None => amount,
+                Some(WtokenBalance(prev_amount)) => amount + prev_amount,
+            };
+            Result::<_, ContractError>::Ok(WtokenBalance(new_amount))
+        })?;
+
+        Ok(())
+    }
+
+    // Emptied entries are removed so that distributions don't iterate over them
+    fn decrease_balance(
+        storage: &mut dyn Storage,
+        height: u64,
+        addr: Addr,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        let prev_amount = WTOKEN_BALANCES
+            .may_load(storage, addr.clone())?
+            .map(|WtokenBalance(prev_amount)| prev_amount)
+            .unwrap_or_default();
+        let new_amount = prev_amount.checked_sub(amount)?;
+
+        if new_amount.is_zero() {
+            WTOKEN_BALANCES.remove(storage, addr, height)?;
+        } else {
+            WTOKEN_BALANCES.save(storage, addr, &WtokenBalance(new_amount), height)?;
+        }
+
+        Ok(())
+    }
+
+    // We assume receiving USDsim every 24 hours, `epoch_length` can enforce a minimum interval
+    pub fn receive_usdsim(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if config.paused {
+            return Err(ContractError::Paused);
+        }
+        if let Some(funders) = &config.funders {
+            if !funders.contains(&info.sender) {
+                return Err(ContractError::NotFunder {
+                    address: info.sender,
+                });
+            }
+        }
+        if let Some(last) = LAST_DISTRIBUTION.may_load(deps.storage)? {
+            let next = last.plus_seconds(config.epoch_length);
+            if env.block.time < next {
+                return Err(ContractError::EpochNotOver { next });
+            }
+        }
+
+        let sent_usdsim = info.funds.iter().find(|coin| coin.denom == USDSIM_DENOM);
+
+        if let Some(coin) = sent_usdsim {
+            LAST_DISTRIBUTION.save(deps.storage, &env.block.time)?;
+            receive_usdsim_inner(deps.storage, env.block.time, coin.amount)
+        } else {
+            Err(ContractError::NoUsdsimTokensSent)
+        }
+    }
+
+    fn receive_usdsim_inner(
+        storage: &mut dyn Storage,
+        now: Timestamp,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        if amount == Uint128::zero() {
+            return Ok(Response::new());
+        }
+
+        let config = CONFIG.load(storage)?;
+        let total_balance = WTOEKN_TOTAL_BALANCE.load(storage)?;
+        let remaining_usdsim = REMAINING_USDSIM.load(storage)?;
+        let UsdsimBalance(forfeited) = FORFEITED_USDSIM.load(storage)?;
+        let distributed = amount + forfeited;
+        let vesting_end = now.plus_seconds(config.reward_vesting_period);
+
+        let balances = WTOKEN_BALANCES
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let (remaining_amount, protocol_amount) = balances.into_iter().try_fold(
+            (distributed + remaining_usdsim.0, Uint128::zero()),
+            |(remaining_amount, protocol_amount),
+             (recipient_addr, balance)|
+             -> Result<_, ContractError> {
+                let reward_amount = balance.0.checked_mul(distributed)? / total_balance.0;
+                let fee_amount = reward_amount.mul_floor(config.protocol_fee);
+
+                let referral_amount = match REFERRERS.may_load(storage, recipient_addr.clone())? {
+                    Some(referrer) => {
+                        let referral_amount = reward_amount.mul_floor(config.referral_share);
+                        credit_referrer(storage, now, referrer, referral_amount)?;
+                        referral_amount
+                    }
+                    None => Uint128::zero(),
+                };
+
+                let mut rewards = USDSIM_REWARDS
+                    .may_load(storage, recipient_addr.clone())?
+                    .unwrap_or_default();
+                rewards.settle(now);
+                rewards.add(now, reward_amount - fee_amount, vesting_end);
+                USDSIM_REWARDS.save(storage, recipient_addr, &rewards)?;
+
+                Ok((
+                    remaining_amount - reward_amount,
+                    protocol_amount + fee_amount - referral_amount,
+                ))
+            },
+        )?;
+
+        REMAINING_USDSIM.save(storage, &UsdsimBalance(remaining_amount))?;
+        FORFEITED_USDSIM.save(storage, &UsdsimBalance(Uint128::zero()))?;
+
+        if protocol_amount.is_zero() {
+            return Ok(Response::new());
+        }
+
+        Ok(Response::new().add_message(BankMsg::Send {
+            to_address: config.fee_collector.into(),
+            amount: coins(protocol_amount.into(), USDSIM_DENOM),
+        }))
+    }
+
+    // Referral rewards are claimable right away
+    fn credit_referrer(
+        storage: &mut dyn Storage,
+        now: Timestamp,
+        referrer: Addr,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        if amount.is_zero() {
+            return Ok(());
+        }
+
+        let mut rewards = USDSIM_REWARDS
+            .may_load(storage, referrer.clone())?
+            .unwrap_or_default();
+        rewards.settle(now);
+        rewards.vested += amount;
+        USDSIM_REWARDS.save(storage, referrer.clone(), &rewards)?;
+
+        REFERRAL_EARNINGS.update(storage, referrer, |may_earnings| {
+            let UsdsimBalance(prev_amount) = may_earnings.unwrap_or(UsdsimBalance(Uint128::zero()));
+            Result::<_, ContractError>::Ok(UsdsimBalance(prev_amount + amount))
+        })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        coins, from_json,
+        testing::{mock_dependencies_with_balance, mock_env, mock_info, MockApi},
+        Addr, BankMsg, ContractResult, MsgResponse, SubMsgResponse, SubMsgResult, SystemError,
+        SystemResult, Uint128, WasmQuery,
+    };
+    use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+    use lazy_static::lazy_static;
+    use serde::de::DeserializeOwned;
+    use swtoken::msg::ReceiptTransferHookMsg;
+
+    use crate::{
+        execute,
+        msg::{
+            InfoResponse, ReceiveMsg, RewardsResponse, TotalPowerAtHeightResponse,
+            VotingPowerAtHeightResponse,
+        },
+        query, reply,
+        state::{UsdsimBalance, WtokenBalance, MAX_VESTING_TRANCHES, USDSIM_REWARDS},
+    };
+
+    use super::*;
+
+    pub fn query2<T>(deps: Deps, msg: QueryMsg) -> StdResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        let env = mock_env();
+        let data = query(deps, env, msg)?;
+        from_json(data)
+    }
+
+    lazy_static! {
+        static ref WTOKEN_ADDRESS: String = {
+            let mock_api = MockApi::default();
+            mock_api.addr_make("wtoken-address").to_string()
+        };
+        static ref RECEIPT_TOKEN_ADDRESS: String = {
+            let mock_api = MockApi::default();
+            mock_api.addr_make("receipt-token-address").to_string()
+        };
+    }
+
+    const RECEIPT_TOKEN_CODE_ID: u64 = 7;
+
+    // Protobuf encoded `MsgInstantiateContractResponse` as returned by wasmd
+    fn receipt_token_instantiate_reply(address: &str) -> Reply {
+        let mut data = vec![0x0a, address.len() as u8];
+        data.extend_from_slice(address.as_bytes());
+
+        #[allow(deprecated)]
+        let result = SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+            msg_responses: vec![MsgResponse {
+                type_url: "/cosmwasm.wasm.v1.MsgInstantiateContractResponse".into(),
+                value: data.into(),
+            }],
+        });
+
+        Reply {
+            id: INSTANTIATE_RECEIPT_TOKEN_REPLY_ID,
+            payload: Binary::default(),
+            gas_used: 0,
+            result,
+        }
+    }
+
+    fn do_instantiation(deps: DepsMut) {
+        do_instantiation_with_vesting(deps, 0)
+    }
+
+    fn do_instantiation_with_vesting(mut deps: DepsMut, reward_vesting_period: u64) {
+        let instantiate_msg = InstantiateMsg {
+            wtoken_contract: WTOKEN_ADDRESS.clone(),
+            receipt_token_code_id: RECEIPT_TOKEN_CODE_ID,
+            max_stake_per_user: None,
+            max_total_stake: None,
+            staking_access: None,
+            reward_vesting_period,
+            protocol_fee: Decimal::zero(),
+            referral_share: Decimal::zero(),
+            fee_collector: None,
+        };
+        let info = mock_info("creator", &[]);
+        let env = mock_env();
+        let res = instantiate(deps.branch(), env.clone(), info, instantiate_msg).unwrap();
+        assert_eq!(1, res.messages.len());
+
+        let msg = receipt_token_instantiate_reply(&RECEIPT_TOKEN_ADDRESS);
+        reply(deps, env, msg).unwrap();
+    }
+
+    fn execute_receive_wtoken(
+        deps: DepsMut,
+        sender: &str,
+        amount: u128,
+    ) -> Result<Response, ContractError> {
+        let env = mock_env();
+        let info = mock_info(&WTOKEN_ADDRESS, &[]);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.into(),
+            amount: amount.into(),
+            msg: Binary::default(),
+        });
+        execute(deps, env.clone(), info, msg)
+    }
+
+    fn execute_stake_with_referrer(
+        deps: DepsMut,
+        sender: &str,
+        amount: u128,
+        referrer: &str,
+    ) -> Result<Response, ContractError> {
+        let env = mock_env();
+        let info = mock_info(&WTOKEN_ADDRESS, &[]);
+        let stake_msg = ReceiveMsg::Stake {
+            referrer: Some(referrer.into()),
+        };
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.into(),
+            amount: amount.into(),
+            msg: to_json_binary(&stake_msg).unwrap(),
+        });
+        execute(deps, env, info, msg)
+    }
+
+    fn execute_unstake(
+        deps: DepsMut,
+        env: Env,
+        sender: &str,
+        amount: u128,
+    ) -> Result<Response, ContractError> {
+        let info = mock_info(&RECEIPT_TOKEN_ADDRESS, &[]);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.into(),
+            amount: amount.into(),
+            msg: Binary::default(),
+        });
+        execute(deps, env.clone(), info, msg)
+    }
+
+    fn execute_receipt_transfer(
+        deps: DepsMut,
+        from: &str,
+        to: &str,
+        amount: u128,
+    ) -> Result<Response, ContractError> {
+        let env = mock_env();
+        let info = mock_info(&RECEIPT_TOKEN_ADDRESS, &[]);
+        let msg = ExecuteMsg::ReceiptTransferHook(ReceiptTransferHookMsg {
+            from: from.into(),
+            to: to.into(),
+            amount: amount.into(),
+        });
+        execute(deps, env.clone(), info, msg)
+    }
+
+    fn execute_receive_usdsim(
+        deps: DepsMut,
+        sender: &str,
+        amount: u128,
+    ) -> Result<Response, ContractError> {
+        let env = mock_env();
+        let info = mock_info(sender, &coins(amount, USDSIM_DENOM));
+        let msg = ExecuteMsg::ReceiveUsdsim {};
+        execute(deps, env.clone(), info, msg)
+    }
+
+    fn query_rewards(deps: Deps, env: Env, address: &str) -> RewardsResponse {
+        let msg = QueryMsg::Rewards {
+            address: address.into(),
+        };
+        from_json(query(deps, env, msg).unwrap()).unwrap()
+    }
+
+    fn vested(vested: u128) -> RewardsResponse {
+        RewardsResponse {
+            vested: vested.into(),
+            unvested: Uint128::zero(),
+        }
+    }
+
+    #[test]
+    fn instantiation() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+
+        let instantiate_msg = InstantiateMsg {
+            wtoken_contract: WTOKEN_ADDRESS.clone(),
+            receipt_token_code_id: RECEIPT_TOKEN_CODE_ID,
+            max_stake_per_user: None,
+            max_total_stake: None,
+            staking_access: None,
+            reward_vesting_period: 0,
+            protocol_fee: Decimal::zero(),
+            referral_share: Decimal::zero(),
+            fee_collector: None,
+        };
+        let info = mock_info("creator", &[]);
+        let env = mock_env();
+        let res = instantiate(deps.as_mut(), env.clone(), info, instantiate_msg).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(
+            res.messages[0],
+            SubMsg::reply_on_success(
+                WasmMsg::Instantiate {
+                    admin: None,
+                    code_id: RECEIPT_TOKEN_CODE_ID,
+                    msg: to_json_binary(&swtoken::msg::InstantiateMsg {
+                        name: RECEIPT_TOKEN_NAME.into(),
+                        symbol: RECEIPT_TOKEN_SYMBOL.into(),
+                        decimals: RECEIPT_TOKEN_DECIMALS,
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                    label: RECEIPT_TOKEN_SYMBOL.into(),
+                },
+                INSTANTIATE_RECEIPT_TOKEN_REPLY_ID
+            )
+        );
+
+        let msg = receipt_token_instantiate_reply(&RECEIPT_TOKEN_ADDRESS);
+        reply(deps.as_mut(), env, msg).unwrap();
+
+        let receipt_token: Addr = query2(deps.as_ref(), QueryMsg::ReceiptToken {}).unwrap();
+        assert_eq!(receipt_token.as_str(), RECEIPT_TOKEN_ADDRESS.as_str());
+
+        let msg = QueryMsg::WtokenTotalBalance {};
+        let total_balance: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
+        assert_eq!(total_balance, WtokenBalance::from(0u128));
+
+        let msg = QueryMsg::RemainingUsdsim {};
+        let remaining_usdsim: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
+        assert_eq!(remaining_usdsim, WtokenBalance::from(0u128));
+    }
+
+    #[test]
+    fn exec_receive_wtoken() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
+
+        let sender = deps.api.addr_make("user0001").to_string();
+        let sender2 = deps.api.addr_make("user0002").to_string();
+
+        // --- execution 1 ---
+
+        execute_receive_wtoken(deps.as_mut(), &sender, 100u128).unwrap();
 
         let msg = QueryMsg::WtokenBalance {
             address: sender.clone(),
         };
         let balance_query_resp: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
 
-        let msg = QueryMsg::WtokenTotalBalance {};
-        let total_balance_query_resp: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
+        let msg = QueryMsg::WtokenTotalBalance {};
+        let total_balance_query_resp: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
+
+        assert_eq!(balance_query_resp, WtokenBalance::from(100u128));
+        assert_eq!(total_balance_query_resp, WtokenBalance::from(100u128));
+
+        // --- execution 2 ---
+
+        execute_receive_wtoken(deps.as_mut(), &sender2, 20u128).unwrap();
+
+        let msg = QueryMsg::WtokenBalance {
+            address: sender2.clone(),
+        };
+        let balance_query_resp2: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
+
+        let msg = QueryMsg::WtokenTotalBalance {};
+        let total_balance_query_resp2: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
+
+        assert_eq!(balance_query_resp2, WtokenBalance::from(20u128));
+        assert_eq!(total_balance_query_resp2, WtokenBalance::from(120u128));
+
+        // --- execution 3 ---
+
+        execute_receive_wtoken(deps.as_mut(), &sender, 50u128).unwrap();
+
+        let msg = QueryMsg::WtokenBalance {
+            address: sender.clone(),
+        };
+        let balance_query_resp: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
+
+        let msg = QueryMsg::WtokenTotalBalance {};
+        let total_balance_query_resp: WtokenBalance = query2(deps.as_ref(), msg).unwrap();
+
+        assert_eq!(balance_query_resp, WtokenBalance::from(150u128));
+        assert_eq!(total_balance_query_resp, WtokenBalance::from(170u128));
+    }
+
+    #[test]
+    fn exec_reward_distribution() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
+
+        let sender1 = deps.api.addr_make("user0001").to_string();
+        let sender2 = deps.api.addr_make("user0002").to_string();
+        let sender3 = deps.api.addr_make("user0003").to_string();
+        let usdsim_sender = deps.api.addr_make("user0004").to_string();
+
+        // receive wtoken 1
+        let res = execute_receive_wtoken(deps.as_mut(), &sender1, 10u128).unwrap();
+        assert_eq!(1, res.messages.len());
+
+        // receive wtoken 2
+        let res = execute_receive_wtoken(deps.as_mut(), &sender2, 90u128).unwrap();
+        assert_eq!(1, res.messages.len());
+
+        // receive usdsim, day 1
+        let res = execute_receive_usdsim(deps.as_mut(), &usdsim_sender, 1000u128).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(
+            query_rewards(deps.as_ref(), mock_env(), &sender1),
+            vested(100)
+        );
+        assert_eq!(
+            query_rewards(deps.as_ref(), mock_env(), &sender2),
+            vested(900)
+        );
+
+        let remaining_usdsim_resp: UsdsimBalance =
+            query2(deps.as_ref(), QueryMsg::RemainingUsdsim {}).unwrap();
+
+        assert_eq!(remaining_usdsim_resp, UsdsimBalance::from(0u128));
+
+        // receive wtoken 3
+        let res = execute_receive_wtoken(deps.as_mut(), &sender3, 100u128).unwrap();
+        assert_eq!(1, res.messages.len());
+
+        // receive usdsim, day 2
+        let res = execute_receive_usdsim(deps.as_mut(), &usdsim_sender, 1001u128).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(
+            query_rewards(deps.as_ref(), mock_env(), &sender1),
+            vested(150)
+        );
+        assert_eq!(
+            query_rewards(deps.as_ref(), mock_env(), &sender2),
+            vested(1350)
+        );
+        assert_eq!(
+            query_rewards(deps.as_ref(), mock_env(), &sender3),
+            vested(500)
+        );
+
+        let remaining_usdsim_resp: UsdsimBalance =
+            query2(deps.as_ref(), QueryMsg::RemainingUsdsim {}).unwrap();
+
+        assert_eq!(remaining_usdsim_resp, UsdsimBalance::from(1u128));
+    }
+
+    #[test]
+    fn exec_receive_fail() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
+
+        let sender = deps.api.addr_make("user0001").to_string();
+        let not_a_wtoken_nor_usdsim_addr = deps.api.addr_make("xxx").to_string();
+
+        let env = mock_env();
+        let info = mock_info(&not_a_wtoken_nor_usdsim_addr, &[]);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.clone(),
+            amount: 100u128.into(),
+            msg: Binary::default(),
+        });
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+
+        assert_eq!(
+            err,
+            ContractError::InvalidWtokenAddress {
+                address: Addr::unchecked(not_a_wtoken_nor_usdsim_addr)
+            },
+        );
+
+        let err = execute_receive_wtoken(deps.as_mut(), &sender, 0u128).unwrap_err();
+
+        assert_eq!(err, ContractError::ZeroAmountReceived);
+    }
+
+    #[test]
+    fn exec_receive_wtoken_mints_receipt() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
+
+        let sender = deps.api.addr_make("user0001").to_string();
+
+        let res = execute_receive_wtoken(deps.as_mut(), &sender, 100u128).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: RECEIPT_TOKEN_ADDRESS.clone(),
+                msg: to_json_binary(&swtoken::msg::ExecuteMsg::Mint {
+                    recipient: sender,
+                    amount: 100u128.into(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })]
+        );
+    }
+
+    #[test]
+    fn exec_receipt_transfer_moves_rewards() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
+
+        let sender1 = deps.api.addr_make("user0001").to_string();
+        let sender2 = deps.api.addr_make("user0002").to_string();
+        let usdsim_sender = deps.api.addr_make("user0004").to_string();
+
+        execute_receive_wtoken(deps.as_mut(), &sender1, 100u128).unwrap();
+        execute_receipt_transfer(deps.as_mut(), &sender1, &sender2, 25u128).unwrap();
+
+        let balance1: WtokenBalance = query2(
+            deps.as_ref(),
+            QueryMsg::WtokenBalance {
+                address: sender1.clone(),
+            },
+        )
+        .unwrap();
+        let balance2: WtokenBalance = query2(
+            deps.as_ref(),
+            QueryMsg::WtokenBalance {
+                address: sender2.clone(),
+            },
+        )
+        .unwrap();
+        let total_balance: WtokenBalance =
+            query2(deps.as_ref(), QueryMsg::WtokenTotalBalance {}).unwrap();
+        assert_eq!(balance1, WtokenBalance::from(75u128));
+        assert_eq!(balance2, WtokenBalance::from(25u128));
+        assert_eq!(total_balance, WtokenBalance::from(100u128));
+
+        execute_receive_usdsim(deps.as_mut(), &usdsim_sender, 1000u128).unwrap();
+        assert_eq!(
+            query_rewards(deps.as_ref(), mock_env(), &sender1),
+            vested(750)
+        );
+        assert_eq!(
+            query_rewards(deps.as_ref(), mock_env(), &sender2),
+            vested(250)
+        );
+
+        // the whole position moved, so the sender no longer takes part in distributions
+        execute_receipt_transfer(deps.as_mut(), &sender1, &sender2, 75u128).unwrap();
+        execute_receive_usdsim(deps.as_mut(), &usdsim_sender, 1000u128).unwrap();
+        assert_eq!(
+            query_rewards(deps.as_ref(), mock_env(), &sender1),
+            vested(750)
+        );
+        assert_eq!(
+            query_rewards(deps.as_ref(), mock_env(), &sender2),
+            vested(1250)
+        );
+
+        // moving more than the staked amount fails
+        let err =
+            execute_receipt_transfer(deps.as_mut(), &sender1, &usdsim_sender, 1u128).unwrap_err();
+        assert!(matches!(err, ContractError::OverflowError(_)));
+    }
+
+    #[test]
+    fn exec_unstake() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
+
+        let mut env = mock_env();
+        env.contract.address = deps.api.addr_make("reward-contract");
+        let contract_addr = env.contract.address.to_string();
+        let sender = deps.api.addr_make("user0001").to_string();
+
+        execute_receive_wtoken(deps.as_mut(), &sender, 100u128).unwrap();
+
+        // sending receipt tokens back moves the stake to the contract first
+        execute_receipt_transfer(deps.as_mut(), &sender, &contract_addr, 40u128).unwrap();
+        let res = execute_unstake(deps.as_mut(), env, &sender, 40u128).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(WasmMsg::Execute {
+                    contract_addr: RECEIPT_TOKEN_ADDRESS.clone(),
+                    msg: to_json_binary(&swtoken::msg::ExecuteMsg::Burn {
+                        amount: 40u128.into()
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }),
+                SubMsg::new(WasmMsg::Execute {
+                    contract_addr: WTOKEN_ADDRESS.clone(),
+                    msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: sender.clone(),
+                        amount: 40u128.into()
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }),
+            ]
+        );
+
+        let balance: WtokenBalance = query2(
+            deps.as_ref(),
+            QueryMsg::WtokenBalance {
+                address: sender.clone(),
+            },
+        )
+        .unwrap();
+        let total_balance: WtokenBalance =
+            query2(deps.as_ref(), QueryMsg::WtokenTotalBalance {}).unwrap();
+        assert_eq!(balance, WtokenBalance::from(60u128));
+        assert_eq!(total_balance, WtokenBalance::from(60u128));
+
+        let contract_balance = query2::<WtokenBalance>(
+            deps.as_ref(),
+            QueryMsg::WtokenBalance {
+                address: contract_addr,
+            },
+        );
+        assert!(contract_balance.is_err());
+    }
+
+    #[test]
+    fn exec_receipt_hook_fail() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
+
+        let sender1 = deps.api.addr_make("user0001").to_string();
+        let sender2 = deps.api.addr_make("user0002").to_string();
 
-        assert_eq!(balance_query_resp, WtokenBalance::from(150u128));
-        assert_eq!(total_balance_query_resp, WtokenBalance::from(170u128));
+        execute_receive_wtoken(deps.as_mut(), &sender1, 100u128).unwrap();
+
+        let info = mock_info(&sender1, &[]);
+        let msg = ExecuteMsg::ReceiptTransferHook(ReceiptTransferHookMsg {
+            from: sender1.clone(),
+            to: sender2,
+            amount: Uint128::new(100),
+        });
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidReceiptTokenAddress {
+                address: Addr::unchecked(sender1)
+            },
+        );
     }
 
     #[test]
-    fn exec_reward_distribution() {
+    fn exec_receive_wtoken_caps() {
         let mut deps = mock_dependencies_with_balance(&[]);
         do_instantiation(deps.as_mut());
 
         let sender1 = deps.api.addr_make("user0001").to_string();
         let sender2 = deps.api.addr_make("user0002").to_string();
         let sender3 = deps.api.addr_make("user0003").to_string();
-        let usdsim_sender = deps.api.addr_make("user0004").to_string();
 
-        // receive wtoken 1
-        let res = execute_receive_wtoken(deps.as_mut(), &sender1, 10u128).unwrap();
-        assert_eq!(0, res.messages.len());
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::UpdateStakeCaps {
+            max_stake_per_user: Some(Uint128::new(100)),
+            max_total_stake: Some(Uint128::new(150)),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        // receive wtoken 2
-        let res = execute_receive_wtoken(deps.as_mut(), &sender2, 90u128).unwrap();
-        assert_eq!(0, res.messages.len());
+        // over the per user cap, the excess is refunded
+        let res = execute_receive_wtoken(deps.as_mut(), &sender1, 120u128).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(WasmMsg::Execute {
+                    contract_addr: RECEIPT_TOKEN_ADDRESS.clone(),
+                    msg: to_json_binary(&swtoken::msg::ExecuteMsg::Mint {
+                        recipient: sender1.clone(),
+                        amount: 100u128.into(),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }),
+                SubMsg::new(WasmMsg::Execute {
+                    contract_addr: WTOKEN_ADDRESS.clone(),
+                    msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: sender1.clone(),
+                        amount: 20u128.into(),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }),
+            ]
+        );
 
-        // receive usdsim, day 1
-        let res = execute_receive_usdsim(deps.as_mut(), &usdsim_sender, 1000u128).unwrap();
-        assert_eq!(2, res.messages.len()); // TODO: add insta.rs for snapshot testing
+        let err = execute_receive_wtoken(deps.as_mut(), &sender1, 1u128).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::UserStakeCapReached {
+                cap: Uint128::new(100)
+            }
+        );
 
-        let remaining_usdsim_resp: UsdsimBalance =
-            query2(deps.as_ref(), QueryMsg::RemainingUsdsim {}).unwrap();
+        // over the total cap
+        let res = execute_receive_wtoken(deps.as_mut(), &sender2, 80u128).unwrap();
+        assert_eq!(res.messages.len(), 2);
 
-        assert_eq!(remaining_usdsim_resp, UsdsimBalance::from(0u128));
+        let balance: WtokenBalance = query2(
+            deps.as_ref(),
+            QueryMsg::WtokenBalance {
+                address: sender2.clone(),
+            },
+        )
+        .unwrap();
+        let total_balance: WtokenBalance =
+            query2(deps.as_ref(), QueryMsg::WtokenTotalBalance {}).unwrap();
+        assert_eq!(balance, WtokenBalance::from(50u128));
+        assert_eq!(total_balance, WtokenBalance::from(150u128));
+
+        let err = execute_receive_wtoken(deps.as_mut(), &sender3, 1u128).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TotalStakeCapReached {
+                cap: Uint128::new(150)
+            }
+        );
 
-        // receive wtoken 3
-        let res = execute_receive_wtoken(deps.as_mut(), &sender3, 100u128).unwrap();
-        assert_eq!(0, res.messages.len());
+        // lifting the caps
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::UpdateStakeCaps {
+            max_stake_per_user: None,
+            max_total_stake: None,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        // receive usdsim, day 2
-        let res = execute_receive_usdsim(deps.as_mut(), &usdsim_sender, 1001u128).unwrap();
-        assert_eq!(3, res.messages.len()); // TODO: add insta.rs for snapshot testing
+        let res = execute_receive_wtoken(deps.as_mut(), &sender3, 1000u128).unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
 
-        let remaining_usdsim_resp: UsdsimBalance =
-            query2(deps.as_ref(), QueryMsg::RemainingUsdsim {}).unwrap();
+    #[test]
+    fn exec_update_stake_caps_fail() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
 
-        assert_eq!(remaining_usdsim_resp, UsdsimBalance::from(1u128));
+        let info = mock_info("not the admin", &[]);
+        let msg = ExecuteMsg::UpdateStakeCaps {
+            max_stake_per_user: Some(Uint128::new(100)),
+            max_total_stake: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+
+        let config: Config = query2(deps.as_ref(), QueryMsg::Config {}).unwrap();
+        assert_eq!(config.max_stake_per_user, None);
+        assert_eq!(config.admin, Addr::unchecked("creator"));
     }
 
     #[test]
-    fn exec_receive_fail() {
+    fn exec_allowlist() {
         let mut deps = mock_dependencies_with_balance(&[]);
         do_instantiation(deps.as_mut());
 
-        let sender = deps.api.addr_make("user0001").to_string();
-        let not_a_wtoken_nor_usdsim_addr = deps.api.addr_make("xxx").to_string();
+        let mut env = mock_env();
+        env.contract.address = deps.api.addr_make("reward-contract");
+        let sender1 = deps.api.addr_make("user0001").to_string();
+        let sender2 = deps.api.addr_make("user0002").to_string();
 
-        let env = mock_env();
-        let info = mock_info(&not_a_wtoken_nor_usdsim_addr, &[]);
-        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: sender.clone(),
+        // everyone can stake until the allowlist is enabled
+        execute_receive_wtoken(deps.as_mut(), &sender1, 100u128).unwrap();
+
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::UpdateStakingAccess {
+            staking_access: StakingAccess::Allowlist,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let can_stake: bool = query2(
+            deps.as_ref(),
+            QueryMsg::CanStake {
+                address: sender1.clone(),
+            },
+        )
+        .unwrap();
+        assert!(!can_stake);
+
+        let err = execute_receive_wtoken(deps.as_mut(), &sender1, 100u128).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotAllowlisted {
+                address: Addr::unchecked(&sender1)
+            }
+        );
+
+        // staked positions can't be handed to addresses that aren't allowed either
+        let err = execute_receipt_transfer(deps.as_mut(), &sender1, &sender2, 10u128).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotAllowlisted {
+                address: Addr::unchecked(&sender2)
+            }
+        );
+
+        // but existing stakers can still exit
+        let contract_addr = env.contract.address.to_string();
+        let info = mock_info(&RECEIPT_TOKEN_ADDRESS, &[]);
+        let msg = ExecuteMsg::ReceiptTransferHook(ReceiptTransferHookMsg {
+            from: sender1.clone(),
+            to: contract_addr,
             amount: 100u128.into(),
-            msg: Binary::default(),
         });
-        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        execute_unstake(deps.as_mut(), env, &sender1, 100u128).unwrap();
+
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::UpdateAllowlist {
+            add: vec![sender1.clone(), sender2.clone()],
+            remove: vec![],
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        execute_receive_wtoken(deps.as_mut(), &sender2, 100u128).unwrap();
 
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::UpdateAllowlist {
+            add: vec![],
+            remove: vec![sender2.clone()],
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let err = execute_receive_wtoken(deps.as_mut(), &sender2, 100u128).unwrap_err();
         assert_eq!(
             err,
-            ContractError::InvalidWtokenAddress {
-                address: Addr::unchecked(not_a_wtoken_nor_usdsim_addr)
+            ContractError::NotAllowlisted {
+                address: Addr::unchecked(&sender2)
+            }
+        );
+
+        let info = mock_info(&sender1, &[]);
+        let msg = ExecuteMsg::UpdateAllowlist {
+            add: vec![sender1.clone()],
+            remove: vec![],
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+    }
+
+    #[test]
+    fn exec_external_access_control() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
+
+        let access_control = deps.api.addr_make("access-control");
+        let allowed = deps.api.addr_make("user0001").to_string();
+        let denied = deps.api.addr_make("user0002").to_string();
+
+        let allowed_addr = allowed.clone();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let AccessControlQueryMsg::IsAllowed { address } = from_json(msg).unwrap();
+                let is_allowed = to_json_binary(&(address == allowed_addr)).unwrap();
+                SystemResult::Ok(ContractResult::Ok(is_allowed))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "only smart queries are mocked".to_string(),
+            }),
+        });
+
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::UpdateStakingAccess {
+            staking_access: StakingAccess::External {
+                contract: access_control,
             },
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        execute_receive_wtoken(deps.as_mut(), &allowed, 100u128).unwrap();
+        let err = execute_receive_wtoken(deps.as_mut(), &denied, 100u128).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotAllowlisted {
+                address: Addr::unchecked(&denied)
+            }
         );
+    }
 
-        let err = execute_receive_wtoken(deps.as_mut(), &sender, 0u128).unwrap_err();
+    #[test]
+    fn exec_reward_vesting() {
+        const DAY: u64 = 24 * 60 * 60;
 
-        assert_eq!(err, ContractError::ZeroAmountReceived);
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation_with_vesting(deps.as_mut(), 10 * DAY);
+
+        let sender1 = deps.api.addr_make("user0001").to_string();
+        let sender2 = deps.api.addr_make("user0002").to_string();
+        let usdsim_sender = deps.api.addr_make("user0004").to_string();
+
+        execute_receive_wtoken(deps.as_mut(), &sender1, 50u128).unwrap();
+        execute_receive_wtoken(deps.as_mut(), &sender2, 50u128).unwrap();
+        execute_receive_usdsim(deps.as_mut(), &usdsim_sender, 2000u128).unwrap();
+
+        let mut env = mock_env();
+        assert_eq!(
+            query_rewards(deps.as_ref(), env.clone(), &sender1),
+            RewardsResponse {
+                vested: Uint128::zero(),
+                unvested: Uint128::new(1000),
+            }
+        );
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&sender1, &[]),
+            ExecuteMsg::ClaimRewards {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoRewardsToClaim);
+
+        // linear release
+        env.block.time = env.block.time.plus_seconds(3 * DAY);
+        assert_eq!(
+            query_rewards(deps.as_ref(), env.clone(), &sender1),
+            RewardsResponse {
+                vested: Uint128::new(300),
+                unvested: Uint128::new(700),
+            }
+        );
+
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&sender1, &[]),
+            ExecuteMsg::ClaimRewards {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: sender1.clone(),
+                amount: coins(300, USDSIM_DENOM),
+            })]
+        );
+        assert_eq!(
+            query_rewards(deps.as_ref(), env.clone(), &sender1),
+            RewardsResponse {
+                vested: Uint128::zero(),
+                unvested: Uint128::new(700),
+            }
+        );
+
+        // sender2 exits half of the position and gives up half of its unvested rewards
+        let info = mock_info(&RECEIPT_TOKEN_ADDRESS, &[]);
+        let msg = ExecuteMsg::ReceiptTransferHook(ReceiptTransferHookMsg {
+            from: sender2.clone(),
+            to: sender1.clone(),
+            amount: 25u128.into(),
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(
+            query_rewards(deps.as_ref(), env.clone(), &sender2),
+            RewardsResponse {
+                vested: Uint128::new(300),
+                unvested: Uint128::new(350),
+            }
+        );
+        let forfeited: UsdsimBalance = query2(deps.as_ref(), QueryMsg::ForfeitedUsdsim {}).unwrap();
+        assert_eq!(forfeited, UsdsimBalance::from(350u128));
+
+        // forfeited rewards go to the stakers of the next distribution
+        let info = mock_info(&usdsim_sender, &coins(1650, USDSIM_DENOM));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::ReceiveUsdsim {},
+        )
+        .unwrap();
+        let forfeited: UsdsimBalance = query2(deps.as_ref(), QueryMsg::ForfeitedUsdsim {}).unwrap();
+        assert_eq!(forfeited, UsdsimBalance::from(0u128));
+        assert_eq!(
+            query_rewards(deps.as_ref(), env.clone(), &sender1),
+            RewardsResponse {
+                vested: Uint128::zero(),
+                unvested: Uint128::new(700 + 1500),
+            }
+        );
+        assert_eq!(
+            query_rewards(deps.as_ref(), env.clone(), &sender2),
+            RewardsResponse {
+                vested: Uint128::new(300),
+                unvested: Uint128::new(350 + 500),
+            }
+        );
+
+        // each distribution vests over its own window
+        env.block.time = env.block.time.plus_seconds(10 * DAY);
+        assert_eq!(
+            query_rewards(deps.as_ref(), env.clone(), &sender1),
+            vested(2200)
+        );
+        assert_eq!(query_rewards(deps.as_ref(), env, &sender2), vested(1150));
+    }
+
+    #[test]
+    fn exec_reward_vesting_per_distribution() {
+        const DAY: u64 = 24 * 60 * 60;
+
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation_with_vesting(deps.as_mut(), 7 * DAY);
+
+        let sender = deps.api.addr_make("user0001").to_string();
+        let usdsim_sender = deps.api.addr_make("user0004").to_string();
+
+        execute_receive_wtoken(deps.as_mut(), &sender, 100u128).unwrap();
+
+        let mut env = mock_env();
+        let start = env.block.time;
+        let info = mock_info(&usdsim_sender, &coins(700, USDSIM_DENOM));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::ReceiveUsdsim {},
+        )
+        .unwrap();
+
+        // a second distribution inside the window of the first one
+        env.block.time = start.plus_seconds(3 * DAY);
+        let info = mock_info(&usdsim_sender, &coins(700, USDSIM_DENOM));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::ReceiveUsdsim {},
+        )
+        .unwrap();
+        assert_eq!(
+            query_rewards(deps.as_ref(), env.clone(), &sender),
+            RewardsResponse {
+                vested: Uint128::new(300),
+                unvested: Uint128::new(400 + 700),
+            }
+        );
+
+        // the first distribution is fully vested at its own end
+        env.block.time = start.plus_seconds(7 * DAY);
+        assert_eq!(
+            query_rewards(deps.as_ref(), env.clone(), &sender),
+            RewardsResponse {
+                vested: Uint128::new(700 + 400),
+                unvested: Uint128::new(300),
+            }
+        );
+
+        env.block.time = start.plus_seconds(10 * DAY);
+        assert_eq!(query_rewards(deps.as_ref(), env, &sender), vested(1400));
+    }
+
+    #[test]
+    fn exec_reward_vesting_tranches_are_capped() {
+        const DAY: u64 = 24 * 60 * 60;
+
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation_with_vesting(deps.as_mut(), 100 * DAY);
+
+        let sender = deps.api.addr_make("user0001");
+        let usdsim_sender = deps.api.addr_make("user0004").to_string();
+
+        execute_receive_wtoken(deps.as_mut(), sender.as_str(), 100u128).unwrap();
+
+        // one distribution a day, all still vesting
+        let mut env = mock_env();
+        let start = env.block.time;
+        for day in 0..2 * MAX_VESTING_TRANCHES as u64 {
+            env.block.time = start.plus_seconds(day * DAY);
+            let info = mock_info(&usdsim_sender, &coins(1000, USDSIM_DENOM));
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                info,
+                ExecuteMsg::ReceiveUsdsim {},
+            )
+            .unwrap();
+        }
+        let rewards = USDSIM_REWARDS.load(&deps.storage, sender.clone()).unwrap();
+        assert_eq!(rewards.tranches.len(), MAX_VESTING_TRANCHES);
+        assert_eq!(rewards.vested + rewards.unvested(), Uint128::new(20_000));
+
+        // the newest tranche vests until the end of the last distribution
+        let last = start.plus_seconds((2 * MAX_VESTING_TRANCHES as u64 - 1) * DAY);
+        env.block.time = last.plus_seconds(100 * DAY);
+        assert_eq!(
+            query_rewards(deps.as_ref(), env, sender.as_str()),
+            vested(20_000)
+        );
+    }
+
+    #[test]
+    fn exec_referral_rewards() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
+
+        let referrer = deps.api.addr_make("referrer").to_string();
+        let referee1 = deps.api.addr_make("user0001").to_string();
+        let referee2 = deps.api.addr_make("user0002").to_string();
+        let staker = deps.api.addr_make("user0003").to_string();
+        let fee_collector = deps.api.addr_make("fee-collector").to_string();
+        let usdsim_sender = deps.api.addr_make("user0004").to_string();
+
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::UpdateFees {
+            protocol_fee: Decimal::percent(10),
+            referral_share: Decimal::percent(4),
+            fee_collector: fee_collector.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        execute_stake_with_referrer(deps.as_mut(), &referee1, 100u128, &referrer).unwrap();
+        execute_stake_with_referrer(deps.as_mut(), &referee2, 100u128, &referrer).unwrap();
+        execute_receive_wtoken(deps.as_mut(), &staker, 100u128).unwrap();
+
+        // the referrer is recorded once, existing stakers can't be referred
+        execute_stake_with_referrer(deps.as_mut(), &referee1, 100u128, &staker).unwrap();
+        execute_stake_with_referrer(deps.as_mut(), &staker, 100u128, &referrer).unwrap();
+
+        let err =
+            execute_stake_with_referrer(deps.as_mut(), &referrer, 100u128, &referrer).unwrap_err();
+        assert_eq!(err, ContractError::SelfReferral);
+
+        let referrals: Vec<Addr> = query2(
+            deps.as_ref(),
+            QueryMsg::Referrals {
+                referrer: referrer.clone(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let mut expected = vec![Addr::unchecked(&referee1), Addr::unchecked(&referee2)];
+        expected.sort();
+        assert_eq!(referrals, expected);
+
+        let referrals: Vec<Addr> = query2(
+            deps.as_ref(),
+            QueryMsg::Referrals {
+                referrer: referrer.clone(),
+                start_after: Some(expected[0].to_string()),
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+        assert_eq!(referrals, vec![expected[1].clone()]);
+
+        // 500 staked in total, referee1 and staker hold 200 each, referee2 100
+        let res = execute_receive_usdsim(deps.as_mut(), &usdsim_sender, 4000u128).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: fee_collector,
+                amount: coins(400 - 64 - 32, USDSIM_DENOM),
+            })]
+        );
+
+        assert_eq!(
+            query_rewards(deps.as_ref(), mock_env(), &referee1),
+            vested(1440)
+        );
+        assert_eq!(
+            query_rewards(deps.as_ref(), mock_env(), &referee2),
+            vested(720)
+        );
+        assert_eq!(
+            query_rewards(deps.as_ref(), mock_env(), &staker),
+            vested(1440)
+        );
+        assert_eq!(
+            query_rewards(deps.as_ref(), mock_env(), &referrer),
+            vested(96)
+        );
+
+        let earnings: UsdsimBalance = query2(
+            deps.as_ref(),
+            QueryMsg::ReferralEarnings {
+                address: referrer.clone(),
+            },
+        )
+        .unwrap();
+        assert_eq!(earnings, UsdsimBalance::from(96u128));
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&referrer, &[]),
+            ExecuteMsg::ClaimRewards {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: referrer,
+                amount: coins(96, USDSIM_DENOM),
+            })]
+        );
+    }
+
+    #[test]
+    fn exec_update_fees_fail() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
+
+        let fee_collector = deps.api.addr_make("fee-collector").to_string();
+
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::UpdateFees {
+            protocol_fee: Decimal::percent(10),
+            referral_share: Decimal::percent(11),
+            fee_collector: fee_collector.clone(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidFees);
+
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::UpdateFees {
+            protocol_fee: Decimal::percent(101),
+            referral_share: Decimal::zero(),
+            fee_collector: fee_collector.clone(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidFees);
+
+        let info = mock_info("not the admin", &[]);
+        let msg = ExecuteMsg::UpdateFees {
+            protocol_fee: Decimal::percent(10),
+            referral_share: Decimal::zero(),
+            fee_collector,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+    }
+
+    #[test]
+    fn admin_can_be_handed_over() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
+        let governance = deps.api.addr_make("governance").to_string();
+
+        let msg = ExecuteMsg::UpdateAdmin {
+            admin: governance.clone(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&governance, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let config: Config = query2(deps.as_ref(), QueryMsg::Config {}).unwrap();
+        assert_eq!(config.admin.as_str(), governance);
+        let msg = ExecuteMsg::UpdateRewardVestingPeriod {
+            reward_vesting_period: 60,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        execute(deps.as_mut(), mock_env(), mock_info(&governance, &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn exec_epoch_funders_and_pause() {
+        const DAY: u64 = 24 * 60 * 60;
+
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
+        let staker = deps.api.addr_make("user0001").to_string();
+        let funder = deps.api.addr_make("funder").to_string();
+        let other = deps.api.addr_make("user0002").to_string();
+        execute_receive_wtoken(deps.as_mut(), &staker, 100u128).unwrap();
+
+        let admin = |deps: DepsMut, msg: ExecuteMsg| {
+            execute(deps, mock_env(), mock_info("creator", &[]), msg).unwrap()
+        };
+        let distribute = |deps: DepsMut, env: &Env, sender: &str| {
+            let info = mock_info(sender, &coins(100, USDSIM_DENOM));
+            execute(deps, env.clone(), info, ExecuteMsg::ReceiveUsdsim {})
+        };
+
+        let msg = ExecuteMsg::Pause {};
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&staker, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+
+        // without funders anyone could take the epoch slot
+        let msg = ExecuteMsg::UpdateEpochLength { epoch_length: DAY };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::EpochWithoutFunders);
+
+        // only the funders distribute, at most once per epoch
+        admin(
+            deps.as_mut(),
+            ExecuteMsg::UpdateFunders {
+                funders: Some(vec![funder.clone()]),
+            },
+        );
+        admin(
+            deps.as_mut(),
+            ExecuteMsg::UpdateEpochLength { epoch_length: DAY },
+        );
+        // a non-funder can't take the slot of the epoch
+        let mut env = mock_env();
+        let err = distribute(deps.as_mut(), &env, &other).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotFunder {
+                address: Addr::unchecked(&other)
+            }
+        );
+        distribute(deps.as_mut(), &env, &funder).unwrap();
+        env.block.time = env.block.time.plus_seconds(DAY - 1);
+        let err = distribute(deps.as_mut(), &env, &funder).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::EpochNotOver {
+                next: mock_env().block.time.plus_seconds(DAY)
+            }
+        );
+        env.block.time = env.block.time.plus_seconds(1);
+        distribute(deps.as_mut(), &env, &funder).unwrap();
+
+        let msg = ExecuteMsg::UpdateFunders { funders: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::EpochWithoutFunders);
+        admin(
+            deps.as_mut(),
+            ExecuteMsg::UpdateEpochLength { epoch_length: 0 },
+        );
+        admin(deps.as_mut(), ExecuteMsg::UpdateFunders { funders: None });
+        env.block.time = env.block.time.plus_seconds(DAY);
+        distribute(deps.as_mut(), &env, &other).unwrap();
+
+        // pausing stops stakes and distributions, not exits and claims
+        admin(deps.as_mut(), ExecuteMsg::Pause {});
+        let config: Config = query2(deps.as_ref(), QueryMsg::Config {}).unwrap();
+        assert!(config.paused);
+        let err = execute_receive_wtoken(deps.as_mut(), &staker, 100u128).unwrap_err();
+        assert_eq!(err, ContractError::Paused);
+        env.block.time = env.block.time.plus_seconds(DAY);
+        let err = distribute(deps.as_mut(), &env, &other).unwrap_err();
+        assert_eq!(err, ContractError::Paused);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&staker, &[]),
+            ExecuteMsg::ClaimRewards {},
+        )
+        .unwrap();
+        env.contract.address = deps.api.addr_make("reward-contract");
+        let contract_addr = env.contract.address.to_string();
+        execute_receipt_transfer(deps.as_mut(), &staker, &contract_addr, 40u128).unwrap();
+        execute_unstake(deps.as_mut(), env.clone(), &staker, 40u128).unwrap();
+
+        admin(deps.as_mut(), ExecuteMsg::Unpause {});
+        execute_receive_wtoken(deps.as_mut(), &staker, 100u128).unwrap();
+    }
+
+    #[test]
+    fn voting_power_follows_stake_history() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiation(deps.as_mut());
+        let mut env = mock_env();
+        env.contract.address = deps.api.addr_make("reward-contract");
+        let contract_addr = env.contract.address.to_string();
+        let sender1 = deps.api.addr_make("user0001").to_string();
+        let sender2 = deps.api.addr_make("user0002").to_string();
+        let start = env.block.height;
+
+        execute_receive_wtoken(deps.as_mut(), &sender1, 100u128).unwrap();
+
+        env.block.height = start + 1;
+        let info = mock_info(&RECEIPT_TOKEN_ADDRESS, &[]);
+        let msg = ExecuteMsg::ReceiptTransferHook(ReceiptTransferHookMsg {
+            from: sender1.clone(),
+            to: sender2.clone(),
+            amount: 40u128.into(),
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        env.block.height = start + 2;
+        let msg = ExecuteMsg::ReceiptTransferHook(ReceiptTransferHookMsg {
+            from: sender2.clone(),
+            to: contract_addr,
+            amount: 40u128.into(),
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        execute_unstake(deps.as_mut(), env.clone(), &sender2, 40u128).unwrap();
+
+        let power = |deps: Deps, address: &str, height: u64| -> Uint128 {
+            let msg = QueryMsg::VotingPowerAtHeight {
+                address: address.into(),
+                height: Some(height),
+            };
+            let res: VotingPowerAtHeightResponse = query2(deps, msg).unwrap();
+            assert_eq!(res.height, height);
+            res.power
+        };
+        let total_power = |deps: Deps, height: u64| -> Uint128 {
+            let msg = QueryMsg::TotalPowerAtHeight {
+                height: Some(height),
+            };
+            query2::<TotalPowerAtHeightResponse>(deps, msg)
+                .unwrap()
+                .power
+        };
+
+        // a height reflects the stakes at the start of that block
+        assert_eq!(power(deps.as_ref(), &sender1, start), Uint128::zero());
+        assert_eq!(total_power(deps.as_ref(), start), Uint128::zero());
+        assert_eq!(power(deps.as_ref(), &sender1, start + 1), Uint128::new(100));
+        assert_eq!(total_power(deps.as_ref(), start + 1), Uint128::new(100));
+        assert_eq!(power(deps.as_ref(), &sender1, start + 2), Uint128::new(60));
+        assert_eq!(power(deps.as_ref(), &sender2, start + 2), Uint128::new(40));
+        assert_eq!(total_power(deps.as_ref(), start + 2), Uint128::new(100));
+        assert_eq!(power(deps.as_ref(), &sender2, start + 3), Uint128::zero());
+        assert_eq!(total_power(deps.as_ref(), start + 3), Uint128::new(60));
+
+        // without a height, the current block is used
+        env.block.height = start + 5;
+        let msg = QueryMsg::VotingPowerAtHeight {
+            address: sender1.clone(),
+            height: None,
+        };
+        let res: VotingPowerAtHeightResponse =
+            from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(
+            res,
+            VotingPowerAtHeightResponse {
+                power: Uint128::new(60),
+                height: start + 5,
+            }
+        );
+
+        let info: InfoResponse = query2(deps.as_ref(), QueryMsg::Info {}).unwrap();
+        assert_eq!(info.info.contract, CONTRACT_NAME);
+        let dao: Addr = query2(deps.as_ref(), QueryMsg::Dao {}).unwrap();
+        assert_eq!(dao, Addr::unchecked("creator"));
     }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::{Addr, OverflowError, StdError};
+use cosmwasm_std::{Addr, OverflowError, StdError, Timestamp, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -9,10 +9,38 @@ pub enum ContractError {
     OverflowError(#[from] OverflowError),
     #[error("{address} is not wtoken address")]
     InvalidWtokenAddress { address: Addr },
+    #[error("{address} is not receipt token address")]
+    InvalidReceiptTokenAddress { address: Addr },
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+    #[error("Failed to parse receipt token instantiation reply")]
+    InvalidReceiptTokenReply,
     #[error("zero amount received")]
     ZeroAmountReceived,
     #[error("No usdsim tokens sent")]
     NoUsdsimTokensSent,
+    #[error("Unauthorized")]
+    Unauthorized,
+    #[error("Stake per user is capped at {cap}")]
+    UserStakeCapReached { cap: Uint128 },
+    #[error("Total stake is capped at {cap}")]
+    TotalStakeCapReached { cap: Uint128 },
+    #[error("{address} is not allowed to stake")]
+    NotAllowlisted { address: Addr },
+    #[error("No vested rewards to claim")]
+    NoRewardsToClaim,
+    #[error("Protocol fee can't exceed 1 and referral share can't exceed the protocol fee")]
+    InvalidFees,
+    #[error("Stakers can't refer themselves")]
+    SelfReferral,
+    #[error("Staking and reward distributions are paused")]
+    Paused,
+    #[error("{address} is not allowed to distribute rewards")]
+    NotFunder { address: Addr },
+    #[error("An epoch length needs a list of funders, anyone could take the epoch otherwise")]
+    EpochWithoutFunders,
+    #[error("The current epoch ends at {next}")]
+    EpochNotOver { next: Timestamp },
 }
 
 pub type ContractResult<T> = Result<T, ContractError>;
@@ -1,7 +1,10 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw2::ContractVersion;
 use cw20::Cw20ReceiveMsg;
+use swtoken::msg::ReceiptTransferHookMsg;
 
-use crate::state::{UsdsimBalance, WtokenBalance};
+use crate::state::{Config, StakingAccess, UsdsimBalance, WtokenBalance};
 
 #[cw_serde]
 #[derive(QueryResponses)]
@@ -12,15 +15,149 @@ pub enum QueryMsg {
     WtokenTotalBalance {},
     #[returns(UsdsimBalance)]
     RemainingUsdsim {},
+    #[returns(Addr)]
+    ReceiptToken {},
+    #[returns(Config)]
+    Config {},
+    /// Whether `address` may currently add to its stake
+    #[returns(bool)]
+    CanStake { address: String },
+    #[returns(RewardsResponse)]
+    Rewards { address: String },
+    /// Unvested rewards waiting for the next distribution
+    #[returns(UsdsimBalance)]
+    ForfeitedUsdsim {},
+    /// Addresses referred by `referrer`
+    #[returns(Vec<Addr>)]
+    Referrals {
+        referrer: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(UsdsimBalance)]
+    ReferralEarnings { address: String },
+    /// Staked Wtoken of `address` at the start of block `height`, the current block if unset.
+    /// This and the queries below make the contract usable as a DAO DAO voting module.
+    #[returns(VotingPowerAtHeightResponse)]
+    VotingPowerAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+    /// Total staked Wtoken at the start of block `height`, the current block if unset
+    #[returns(TotalPowerAtHeightResponse)]
+    TotalPowerAtHeight { height: Option<u64> },
+    #[returns(InfoResponse)]
+    Info {},
+    /// The admin, which governs the reward parameters
+    #[returns(Addr)]
+    Dao {},
+}
+
+#[cw_serde]
+pub struct VotingPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+#[cw_serde]
+pub struct TotalPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+#[cw_serde]
+pub struct InfoResponse {
+    pub info: ContractVersion,
+}
+
+#[cw_serde]
+pub struct RewardsResponse {
+    /// Claimable now
+    pub vested: Uint128,
+    pub unvested: Uint128,
 }
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub wtoken_contract: String,
+    /// Code id of the swtoken contract used as the staking receipt
+    pub receipt_token_code_id: u64,
+    pub max_stake_per_user: Option<Uint128>,
+    pub max_total_stake: Option<Uint128>,
+    /// Defaults to `StakingAccess::Open`
+    pub staking_access: Option<StakingAccess>,
+    /// Seconds over which distributed rewards vest, 0 makes them claimable right away
+    pub reward_vesting_period: u64,
+    pub protocol_fee: Decimal,
+    /// Has to be lower or equal to `protocol_fee`
+    pub referral_share: Decimal,
+    /// Defaults to the instantiator
+    pub fee_collector: Option<String>,
+}
+
+/// Payload of Wtoken sent to this contract. An empty payload stakes without a referrer.
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// `referrer` is only recorded for addresses staking for the first time
+    Stake { referrer: Option<String> },
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
+    /// Wtoken to stake, or receipt tokens to unstake
     Receive(Cw20ReceiveMsg),
     ReceiveUsdsim {},
+    /// Sends the vested USDsim rewards of the sender
+    ClaimRewards {},
+    ReceiptTransferHook(ReceiptTransferHookMsg),
+    /// Only callable by the admin, `None` removes the cap
+    UpdateStakeCaps {
+        max_stake_per_user: Option<Uint128>,
+        max_total_stake: Option<Uint128>,
+    },
+    /// Only callable by the admin
+    UpdateStakingAccess {
+        staking_access: StakingAccess,
+    },
+    /// Only callable by the admin
+    UpdateAllowlist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Only callable by the admin, applies from the next distribution on
+    UpdateRewardVestingPeriod {
+        reward_vesting_period: u64,
+    },
+    /// Only callable by the admin
+    UpdateFees {
+        protocol_fee: Decimal,
+        referral_share: Decimal,
+        fee_collector: String,
+    },
+    /// Only callable by the admin, e.g. to hand the contract over to a governance contract
+    UpdateAdmin {
+        admin: String,
+    },
+    /// Only callable by the admin, minimum seconds between two distributions.
+    /// Anything but 0 requires `funders` to be set.
+    UpdateEpochLength {
+        epoch_length: u64,
+    },
+    /// Only callable by the admin, `None` lets anyone distribute rewards and requires an
+    /// `epoch_length` of 0
+    UpdateFunders {
+        funders: Option<Vec<String>>,
+    },
+    /// Only callable by the admin, stops new stakes and reward distributions until unpaused
+    Pause {},
+    /// Only callable by the admin
+    Unpause {},
+}
+
+/// Query interface an external access control contract has to implement
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum AccessControlQueryMsg {
+    #[returns(bool)]
+    IsAllowed { address: String },
 }
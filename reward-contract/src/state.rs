@@ -1,11 +1,73 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Decimal, Empty, Timestamp, Uint128};
+use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
 
 pub const WTOKEN_CONTRACT: Item<Addr> = Item::new("wtoken_contract");
-pub const WTOKEN_BALANCES: Map<Addr, WtokenBalance> = Map::new("wtoken_balances");
-pub const WTOEKN_TOTAL_BALANCE: Item<WtokenBalance> = Item::new("wtoken_total_balance");
+pub const RECEIPT_TOKEN: Item<Addr> = Item::new("receipt_token");
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const ALLOWLIST: Map<Addr, Empty> = Map::new("allowlist");
+/// Staked Wtoken, checkpointed on every block it changes in for the voting power queries
+pub const WTOKEN_BALANCES: SnapshotMap<Addr, WtokenBalance> = SnapshotMap::new(
+    "wtoken_balances",
+    "wtoken_balances__checkpoints",
+    "wtoken_balances__changelog",
+    Strategy::EveryBlock,
+);
+pub const WTOEKN_TOTAL_BALANCE: SnapshotItem<WtokenBalance> = SnapshotItem::new(
+    "wtoken_total_balance",
+    "wtoken_total_balance__checkpoints",
+    "wtoken_total_balance__changelog",
+    Strategy::EveryBlock,
+);
 pub const REMAINING_USDSIM: Item<UsdsimBalance> = Item::new("remaining_usdsim");
+pub const USDSIM_REWARDS: Map<Addr, VestingRewards> = Map::new("usdsim_rewards");
+/// Unvested rewards given up by exiting stakers, added to the next distribution
+pub const FORFEITED_USDSIM: Item<UsdsimBalance> = Item::new("forfeited_usdsim");
+/// Referee to referrer, set once when the referee first stakes
+pub const REFERRERS: Map<Addr, Addr> = Map::new("referrers");
+/// (referrer, referee), to list the referrals of a referrer
+pub const REFERRALS: Map<(Addr, Addr), Empty> = Map::new("referrals");
+/// Total USDsim credited to each referrer
+pub const REFERRAL_EARNINGS: Map<Addr, UsdsimBalance> = Map::new("referral_earnings");
+/// Time of the last reward distribution, for `Config::epoch_length`
+pub const LAST_DISTRIBUTION: Item<Timestamp> = Item::new("last_distribution");
+
+#[cw_serde]
+pub struct Config {
+    pub admin: Addr,
+    /// Deposits above these caps are partially accepted and the excess is refunded
+    pub max_stake_per_user: Option<Uint128>,
+    pub max_total_stake: Option<Uint128>,
+    pub staking_access: StakingAccess,
+    /// Seconds over which each distribution vests linearly
+    pub reward_vesting_period: u64,
+    /// Share of each staker's reward taken by the protocol
+    pub protocol_fee: Decimal,
+    /// Share of a referee's reward paid to its referrer, out of `protocol_fee`
+    pub referral_share: Decimal,
+    pub fee_collector: Addr,
+    /// Minimum seconds between two reward distributions, 0 for none
+    #[serde(default)]
+    pub epoch_length: u64,
+    /// Addresses allowed to distribute rewards, anyone if unset
+    #[serde(default)]
+    pub funders: Option<Vec<Addr>>,
+    /// Stops new stakes and reward distributions, exiting and claiming still work
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// Who may add to a stake. Exiting is always possible.
+#[cw_serde]
+pub enum StakingAccess {
+    Open,
+    /// Only addresses in `ALLOWLIST`, managed by the admin
+    Allowlist,
+    /// Delegates the decision to an access control contract, see `AccessControlQueryMsg`
+    External {
+        contract: Addr,
+    },
+}
 
 #[cw_serde]
 pub struct WtokenBalance(pub Uint128);
@@ -13,6 +75,100 @@ pub struct WtokenBalance(pub Uint128);
 #[cw_serde]
 pub struct UsdsimBalance(pub Uint128);
 
+/// Bounds the gas of settling a staker's rewards, however often rewards are distributed
+pub const MAX_VESTING_TRANCHES: usize = 10;
+
+/// USDsim rewards of a staker. Each distribution vests over its own window.
+#[cw_serde]
+#[derive(Default)]
+pub struct VestingRewards {
+    /// Vested and not claimed yet
+    pub vested: Uint128,
+    /// One entry per distribution that is still vesting, at most `MAX_VESTING_TRANCHES`
+    pub tranches: Vec<VestingTranche>,
+}
+
+#[cw_serde]
+pub struct VestingTranche {
+    pub unvested: Uint128,
+    pub last_update: Timestamp,
+    pub vesting_end: Timestamp,
+}
+
+impl VestingRewards {
+    pub fn unvested(&self) -> Uint128 {
+        self.tranches.iter().map(|tranche| tranche.unvested).sum()
+    }
+
+    pub fn add(&mut self, now: Timestamp, amount: Uint128, vesting_end: Timestamp) {
+        if amount.is_zero() {
+            return;
+        }
+        if vesting_end <= now {
+            self.vested += amount;
+            return;
+        }
+        let full = self.tranches.len() >= MAX_VESTING_TRANCHES;
+        match self.tranches.last_mut() {
+            // distributions of the same block share a tranche
+            Some(last) if last.vesting_end == vesting_end => {
+                self.vested += last.settle(now);
+                last.unvested += amount;
+            }
+            // the newest tranche takes the distribution and vests until its end
+            Some(last) if full => {
+                self.vested += last.settle(now);
+                last.unvested += amount;
+                last.vesting_end = vesting_end;
+            }
+            _ => self.tranches.push(VestingTranche {
+                unvested: amount,
+                last_update: now,
+                vesting_end,
+            }),
+        }
+    }
+
+    /// Moves what has been released since the last update into `vested`
+    pub fn settle(&mut self, now: Timestamp) {
+        for tranche in &mut self.tranches {
+            let released = tranche.settle(now);
+            self.vested += released;
+        }
+        self.tranches.retain(|tranche| !tranche.unvested.is_zero());
+    }
+
+    /// Takes `numerator / denominator` of every tranche, returns the total taken
+    pub fn forfeit(&mut self, numerator: Uint128, denominator: Uint128) -> Uint128 {
+        let mut forfeited = Uint128::zero();
+        for tranche in &mut self.tranches {
+            let amount = tranche.unvested.multiply_ratio(numerator, denominator);
+            tranche.unvested -= amount;
+            forfeited += amount;
+        }
+        self.tranches.retain(|tranche| !tranche.unvested.is_zero());
+        forfeited
+    }
+}
+
+impl VestingTranche {
+    fn settle(&mut self, now: Timestamp) -> Uint128 {
+        let released = if now >= self.vesting_end {
+            self.unvested
+        } else if now > self.last_update {
+            self.unvested.multiply_ratio(
+                now.seconds() - self.last_update.seconds(),
+                self.vesting_end.seconds() - self.last_update.seconds(),
+            )
+        } else {
+            Uint128::zero()
+        };
+        self.unvested -= released;
+        self.last_update = now;
+        released
+    }
+}
+
 impl From<u128> for WtokenBalance {
     fn from(value: u128) -> Self {
         Self(Uint128::from(value))
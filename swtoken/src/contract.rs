@@ -0,0 +1,311 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cw20::MinterResponse;
+use cw20_base::contract::{
+    execute_burn, execute_mint, execute_send, execute_transfer, query_balance, query_minter,
+    query_token_info,
+};
+
+use crate::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ReceiptTransferHookMsg},
+};
+
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, Uint128,
+};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let InstantiateMsg {
+        name,
+        symbol,
+        decimals,
+    } = msg;
+
+    let cw20_init_msg = cw20_base::msg::InstantiateMsg {
+        name,
+        symbol,
+        initial_balances: vec![],
+        decimals,
+        mint: Some(MinterResponse {
+            minter: info.sender.to_string(),
+            cap: None,
+        }),
+        marketing: None,
+    };
+
+    cw20_base::contract::instantiate(deps, env, info, cw20_init_msg)?;
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    use QueryMsg::*;
+
+    Ok(match msg {
+        TokenInfo {} => to_json_binary(&query_token_info(deps)?)?,
+        Balance { address } => to_json_binary(&query_balance(deps, address)?)?,
+        Minter {} => to_json_binary(&query_minter(deps)?)?,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    use ExecuteMsg::*;
+
+    let minter = load_minter(deps.as_ref())?;
+
+    Ok(match msg {
+        Transfer { recipient, amount } => {
+            // A plain transfer would strand the stake on the minter's own entry
+            if recipient == minter.as_str() {
+                return Err(ContractError::TransferToMinter);
+            }
+            let hook = transfer_hook(&minter, &info.sender, &recipient, amount)?;
+            execute_transfer(deps, env, info, recipient, amount)?.add_message(hook)
+        }
+        Send {
+            contract,
+            amount,
+            msg,
+        } => {
+            // The minter must see the balance change before the receiver reacts to it
+            let hook = transfer_hook(&minter, &info.sender, &contract, amount)?;
+            let res = execute_send(deps, env, info, contract, amount, msg)?;
+            Response::new()
+                .add_message(hook)
+                .add_submessages(res.messages)
+                .add_attributes(res.attributes)
+        }
+        Mint { recipient, amount } => execute_mint(deps, env, info, recipient, amount)?,
+        Burn { amount } => {
+            if info.sender != minter {
+                return Err(ContractError::Unauthorized);
+            }
+            execute_burn(deps, env, info, amount)?
+        }
+    })
+}
+
+fn load_minter(deps: Deps) -> Result<Addr, ContractError> {
+    let minter = query_minter(deps)?.ok_or(ContractError::Unauthorized)?;
+    Ok(Addr::unchecked(minter.minter))
+}
+
+fn transfer_hook(
+    minter: &Addr,
+    from: &Addr,
+    to: &str,
+    amount: Uint128,
+) -> Result<CosmosMsg, ContractError> {
+    let hook = ReceiptTransferHookMsg {
+        from: from.to_string(),
+        to: to.to_string(),
+        amount,
+    };
+    Ok(hook.into_cosmos_msg(minter)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::CW20BaseError;
+    use cosmwasm_std::{
+        testing::{mock_dependencies, mock_env, mock_info},
+        StdError, SubMsg, WasmMsg,
+    };
+    use cw20::{Cw20ReceiveMsg, TokenInfoResponse};
+
+    use super::*;
+
+    fn get_balance<T: Into<String>>(deps: Deps, address: T) -> Uint128 {
+        query_balance(deps, address.into()).unwrap().balance
+    }
+
+    fn do_instantiate(mut deps: DepsMut, minter: &str) {
+        let instantiate_msg = InstantiateMsg {
+            name: "Staked Wtoken".into(),
+            symbol: "sWTK".into(),
+            decimals: 6,
+        };
+        let info = mock_info(minter, &[]);
+        let env = mock_env();
+        let res = instantiate(deps.branch(), env, info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap(),
+            TokenInfoResponse {
+                name: "Staked Wtoken".into(),
+                symbol: "sWTK".into(),
+                decimals: 6,
+                total_supply: Uint128::zero(),
+            }
+        );
+        assert_eq!(
+            query_minter(deps.as_ref()).unwrap().unwrap().minter,
+            minter.to_string()
+        );
+    }
+
+    fn do_mint(deps: DepsMut, minter: &str, recipient: &str, amount: u128) {
+        let msg = ExecuteMsg::Mint {
+            recipient: recipient.into(),
+            amount: amount.into(),
+        };
+        execute(deps, mock_env(), mock_info(minter, &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn only_minter_can_mint_and_burn() {
+        let mut deps = mock_dependencies();
+        let minter = deps.api.addr_make("reward-contract").to_string();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        do_instantiate(deps.as_mut(), &minter);
+
+        let msg = ExecuteMsg::Mint {
+            recipient: addr1.clone(),
+            amount: 100u128.into(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::CW20Base(CW20BaseError::Unauthorized {}));
+
+        do_mint(deps.as_mut(), &minter, &addr1, 100);
+        assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(100));
+
+        let msg = ExecuteMsg::Burn {
+            amount: 100u128.into(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+
+        do_mint(deps.as_mut(), &minter, &minter, 40);
+        let msg = ExecuteMsg::Burn {
+            amount: 40u128.into(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &minter), Uint128::zero());
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap().total_supply,
+            Uint128::new(100)
+        );
+    }
+
+    #[test]
+    fn transfer_notifies_minter() {
+        let mut deps = mock_dependencies();
+        let minter = deps.api.addr_make("reward-contract").to_string();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let addr2 = deps.api.addr_make("addr0002").to_string();
+        do_instantiate(deps.as_mut(), &minter);
+        do_mint(deps.as_mut(), &minter, &addr1, 100);
+
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: 30u128.into(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(
+                ReceiptTransferHookMsg {
+                    from: addr1.clone(),
+                    to: addr2.clone(),
+                    amount: 30u128.into(),
+                }
+                .into_cosmos_msg(&minter)
+                .unwrap()
+            )]
+        );
+        assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(70));
+        assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(30));
+
+        // failed transfers do not notify anyone
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr1,
+            amount: 31u128.into(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&addr2, &[]), msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::CW20Base(CW20BaseError::Std(StdError::Overflow { .. }))
+        ));
+    }
+
+    #[test]
+    fn transfer_to_minter_fails() {
+        let mut deps = mock_dependencies();
+        let minter = deps.api.addr_make("reward-contract").to_string();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        do_instantiate(deps.as_mut(), &minter);
+        do_mint(deps.as_mut(), &minter, &addr1, 100);
+
+        let msg = ExecuteMsg::Transfer {
+            recipient: minter.clone(),
+            amount: 30u128.into(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::TransferToMinter);
+        assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(100));
+        assert_eq!(get_balance(deps.as_ref(), &minter), Uint128::zero());
+    }
+
+    #[test]
+    fn send_notifies_minter_before_receiver() {
+        let mut deps = mock_dependencies();
+        let minter = deps.api.addr_make("reward-contract").to_string();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let contract = deps.api.addr_make("contract0001").to_string();
+        let send_msg = Binary::from(r#"{"some":123}"#.as_bytes());
+        do_instantiate(deps.as_mut(), &minter);
+        do_mint(deps.as_mut(), &minter, &addr1, 100);
+
+        let msg = ExecuteMsg::Send {
+            contract: contract.clone(),
+            amount: 60u128.into(),
+            msg: send_msg.clone(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(
+                ReceiptTransferHookMsg {
+                    from: addr1.clone(),
+                    to: contract.clone(),
+                    amount: 60u128.into(),
+                }
+                .into_cosmos_msg(&minter)
+                .unwrap()
+            )
+        );
+        let binary_msg = Cw20ReceiveMsg {
+            sender: addr1.clone(),
+            amount: 60u128.into(),
+            msg: send_msg,
+        }
+        .into_json_binary()
+        .unwrap();
+        assert_eq!(
+            res.messages[1],
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract.clone(),
+                msg: binary_msg,
+                funds: vec![],
+            }))
+        );
+        assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(40));
+        assert_eq!(get_balance(deps.as_ref(), &contract), Uint128::new(60));
+    }
+}
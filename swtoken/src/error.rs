@@ -0,0 +1,17 @@
+use cosmwasm_std::StdError;
+pub use cw20_base::ContractError as CW20BaseError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    StdError(#[from] StdError),
+    #[error("CW20Base error: {0}")]
+    CW20Base(#[from] CW20BaseError),
+    #[error("Unauthorized")]
+    Unauthorized,
+    #[error("Receipt tokens can only be returned to the minter with Send")]
+    TransferToMinter,
+}
+
+pub type ContractResult<T> = Result<T, ContractError>;
@@ -0,0 +1,74 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{to_json_binary, Binary, CosmosMsg, StdResult, Uint128, WasmMsg};
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(cw20::BalanceResponse)]
+    Balance { address: String },
+    #[returns(cw20::TokenInfoResponse)]
+    TokenInfo {},
+    #[returns(cw20::MinterResponse)]
+    Minter {},
+}
+
+/// The instantiating contract becomes the minter and receives transfer hooks.
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    Transfer {
+        recipient: String,
+        amount: Uint128,
+    },
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Only callable by the minter
+    Mint {
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Only callable by the minter, burns from its own balance
+    Burn {
+        amount: Uint128,
+    },
+}
+
+/// Sent to the minter whenever receipt tokens move between two holders.
+#[cw_serde]
+pub struct ReceiptTransferHookMsg {
+    pub from: String,
+    pub to: String,
+    pub amount: Uint128,
+}
+
+impl ReceiptTransferHookMsg {
+    pub fn into_json_binary(self) -> StdResult<Binary> {
+        let msg = HookExecuteMsg::ReceiptTransferHook(self);
+        to_json_binary(&msg)
+    }
+
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_json_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+// This is just a helper to properly serialize the above message
+#[cw_serde]
+enum HookExecuteMsg {
+    ReceiptTransferHook(ReceiptTransferHookMsg),
+}
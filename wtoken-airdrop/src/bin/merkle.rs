@@ -0,0 +1,90 @@
+//! Builds the merkle root and the proofs of an airdrop stage from a CSV of
+//! `address,amount` lines, a header line is skipped. Leaves and nodes are hashed
+//! by `wtoken_airdrop::merkle`, the same code the contract verifies claims with.
+//!
+//! `cargo run --bin merkle -- airdrop.csv > airdrop.json`
+
+use std::{collections::HashSet, env, fs, process};
+
+use cosmwasm_std::{to_json_string, Uint128};
+use serde::Serialize;
+use wtoken_airdrop::merkle::{leaf_hash, MerkleTree};
+
+#[derive(Serialize)]
+struct Airdrop {
+    /// `merkle_root` and `total_amount` of `RegisterMerkleRoot`
+    merkle_root: String,
+    total_amount: Uint128,
+    claims: Vec<Claim>,
+}
+
+/// `amount` and `proof` of `Claim`
+#[derive(Serialize)]
+struct Claim {
+    address: String,
+    amount: Uint128,
+    proof: Vec<String>,
+}
+
+fn parse_csv(csv: &str) -> Result<Vec<(String, Uint128)>, String> {
+    let mut seen = HashSet::new();
+    let mut entries = vec![];
+    for (index, line) in csv.lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (address, amount) = line
+            .split_once(',')
+            .ok_or_else(|| format!("line {line_no}: expected `address,amount`"))?;
+        let (address, amount) = (address.trim(), amount.trim());
+        let amount = match amount.parse::<u128>() {
+            Ok(amount) => Uint128::new(amount),
+            Err(_) if index == 0 => continue,
+            Err(err) => return Err(format!("line {line_no}: invalid amount {amount}: {err}")),
+        };
+        if !seen.insert(address.to_string()) {
+            return Err(format!("line {line_no}: duplicate address {address}"));
+        }
+        entries.push((address.to_string(), amount));
+    }
+    if entries.is_empty() {
+        return Err("no airdrop entries".into());
+    }
+    Ok(entries)
+}
+
+fn main() {
+    let Some(path) = env::args().nth(1) else {
+        eprintln!("usage: merkle <airdrop.csv>");
+        process::exit(1);
+    };
+    let entries = fs::read_to_string(&path)
+        .map_err(|err| format!("{path}: {err}"))
+        .and_then(|csv| parse_csv(&csv))
+        .unwrap_or_else(|err| {
+            eprintln!("{err}");
+            process::exit(1);
+        });
+
+    let leaves = entries
+        .iter()
+        .map(|(address, amount)| leaf_hash(address, *amount))
+        .collect();
+    let tree = MerkleTree::new(leaves);
+    let airdrop = Airdrop {
+        merkle_root: hex::encode(tree.root()),
+        total_amount: entries.iter().map(|(_, amount)| amount).sum(),
+        claims: entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, (address, amount))| Claim {
+                address,
+                amount,
+                proof: tree.proof(index).iter().map(hex::encode).collect(),
+            })
+            .collect(),
+    };
+    println!("{}", to_json_string(&airdrop).unwrap());
+}
@@ -0,0 +1,706 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage,
+};
+
+use crate::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{Config, Stage, CONFIG, LATEST_STAGE, STAGES},
+};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let InstantiateMsg { owner, wtoken } = msg;
+    let owner = match owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+    let config = Config {
+        owner,
+        wtoken: deps.api.addr_validate(&wtoken)?,
+    };
+    CONFIG.save(deps.storage, &config)?;
+    LATEST_STAGE.save(deps.storage, &0)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "instantiate")
+        .add_attribute("owner", config.owner)
+        .add_attribute("wtoken", config.wtoken))
+}
+
+fn load_stage(storage: &dyn Storage, stage: u64) -> Result<Stage, ContractError> {
+    STAGES
+        .may_load(storage, stage)?
+        .ok_or(ContractError::StageNotFound { stage })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    use ExecuteMsg::*;
+    match msg {
+        Receive(receive_msg) => exec::receive(deps, env, info, receive_msg),
+        RegisterMerkleRoot {
+            merkle_root,
+            expiration,
+            total_amount,
+        } => exec::register_merkle_root(deps, env, info, merkle_root, expiration, total_amount),
+        Claim {
+            stage,
+            amount,
+            proof,
+        } => exec::claim(deps, env, info, stage, amount, proof),
+        Clawback { stage, recipient } => exec::clawback(deps, env, info, stage, recipient),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    use QueryMsg::*;
+    Ok(match msg {
+        Config {} => to_json_binary(&CONFIG.load(deps.storage)?)?,
+        Stage { stage } => to_json_binary(&STAGES.load(deps.storage, stage)?)?,
+        LatestStage {} => to_json_binary(&query::latest_stage(deps)?)?,
+        IsClaimed { stage, address } => to_json_binary(&query::is_claimed(deps, stage, address)?)?,
+    })
+}
+
+mod query {
+    use crate::{
+        msg::{IsClaimedResponse, LatestStageResponse},
+        state::CLAIMS,
+    };
+
+    use super::*;
+
+    pub fn latest_stage(deps: Deps) -> StdResult<LatestStageResponse> {
+        Ok(LatestStageResponse {
+            latest_stage: LATEST_STAGE.load(deps.storage)?,
+        })
+    }
+
+    pub fn is_claimed(deps: Deps, stage: u64, address: String) -> StdResult<IsClaimedResponse> {
+        let address = deps.api.addr_validate(&address)?;
+        Ok(IsClaimedResponse {
+            is_claimed: CLAIMS.has(deps.storage, (stage, &address)),
+        })
+    }
+}
+
+mod exec {
+    use cosmwasm_std::{from_json, Addr, Empty, Uint128, WasmMsg};
+    use cw20::{Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, MinterResponse};
+    use cw_utils::Expiration;
+
+    use crate::{
+        merkle::{leaf_hash, verify, Hash},
+        msg::ReceiveMsg,
+        state::CLAIMS,
+    };
+
+    use super::*;
+
+    fn decode_hash(hex_hash: &str) -> Option<Hash> {
+        hex::decode(hex_hash).ok()?.try_into().ok()
+    }
+
+    fn wtoken_transfer(wtoken: String, recipient: String, amount: Uint128) -> StdResult<WasmMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: wtoken,
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer { recipient, amount })?,
+            funds: vec![],
+        })
+    }
+
+    /// Adds the received Wtoken to the funds of a stage
+    pub fn receive(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: Cw20ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if info.sender != config.wtoken {
+            return Err(ContractError::InvalidWtokenAddress {
+                address: info.sender,
+            });
+        }
+        if msg.amount.is_zero() {
+            return Err(ContractError::ZeroAmountReceived);
+        }
+
+        let sender = deps.api.addr_validate(&msg.sender)?;
+        if sender != config.owner && !is_wtoken_minter(deps.as_ref(), &config.wtoken, &sender)? {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let ReceiveMsg::Fund { stage: stage_id } = from_json(&msg.msg)?;
+        let mut stage = load_stage(deps.storage, stage_id)?;
+        if stage.expiration.is_expired(&env.block) {
+            return Err(ContractError::StageExpired { stage: stage_id });
+        }
+        let room = stage.total_amount.saturating_sub(stage.funded);
+        if room.is_zero() {
+            return Err(ContractError::StageFullyFunded { stage: stage_id });
+        }
+        let accepted = msg.amount.min(room);
+        let refund_amount = msg.amount - accepted;
+        stage.funded += accepted;
+        STAGES.save(deps.storage, stage_id, &stage)?;
+
+        let res = Response::new()
+            .add_attribute("action", "fund")
+            .add_attribute("stage", stage_id.to_string())
+            .add_attribute("from", &sender)
+            .add_attribute("amount", accepted);
+        if refund_amount.is_zero() {
+            return Ok(res);
+        }
+
+        Ok(res
+            .add_message(wtoken_transfer(
+                config.wtoken.into(),
+                sender.into(),
+                refund_amount,
+            )?)
+            .add_attribute("refunded", refund_amount))
+    }
+
+    fn is_wtoken_minter(deps: Deps, wtoken: &Addr, address: &Addr) -> StdResult<bool> {
+        let minter: Option<MinterResponse> = deps
+            .querier
+            .query_wasm_smart(wtoken, &Cw20QueryMsg::Minter {})?;
+        Ok(minter.is_some_and(|minter| minter.minter == address.as_str()))
+    }
+
+    pub fn register_merkle_root(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        merkle_root: String,
+        expiration: Option<Expiration>,
+        total_amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        if info.sender != CONFIG.load(deps.storage)?.owner {
+            return Err(ContractError::Unauthorized);
+        }
+        if decode_hash(&merkle_root).is_none() {
+            return Err(ContractError::InvalidMerkleRoot);
+        }
+        let stage_id = LATEST_STAGE.load(deps.storage)? + 1;
+        let expiration = expiration.unwrap_or_default();
+        if expiration.is_expired(&env.block) {
+            return Err(ContractError::StageExpired { stage: stage_id });
+        }
+
+        let stage = Stage {
+            merkle_root,
+            expiration,
+            total_amount,
+            funded: Uint128::zero(),
+            claimed: Uint128::zero(),
+            clawed_back: Uint128::zero(),
+        };
+        STAGES.save(deps.storage, stage_id, &stage)?;
+        LATEST_STAGE.save(deps.storage, &stage_id)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "register_merkle_root")
+            .add_attribute("stage", stage_id.to_string())
+            .add_attribute("merkle_root", stage.merkle_root)
+            .add_attribute("total_amount", total_amount))
+    }
+
+    pub fn claim(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        stage_id: u64,
+        amount: Uint128,
+        proof: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        let mut stage = load_stage(deps.storage, stage_id)?;
+        if stage.expiration.is_expired(&env.block) {
+            return Err(ContractError::StageExpired { stage: stage_id });
+        }
+        if CLAIMS.has(deps.storage, (stage_id, &info.sender)) {
+            return Err(ContractError::AlreadyClaimed);
+        }
+
+        let proof = proof
+            .iter()
+            .map(|hash| decode_hash(hash))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ContractError::InvalidProof)?;
+        let root = decode_hash(&stage.merkle_root).ok_or(ContractError::InvalidMerkleRoot)?;
+        if !verify(leaf_hash(info.sender.as_str(), amount), &proof, &root) {
+            return Err(ContractError::VerificationFailed);
+        }
+
+        let available = stage.available();
+        if amount > available {
+            return Err(ContractError::InsufficientStageFunds {
+                stage: stage_id,
+                available,
+            });
+        }
+        stage.claimed += amount;
+        STAGES.save(deps.storage, stage_id, &stage)?;
+        CLAIMS.save(deps.storage, (stage_id, &info.sender), &Empty {})?;
+
+        let wtoken = CONFIG.load(deps.storage)?.wtoken;
+        Ok(Response::new()
+            .add_message(wtoken_transfer(
+                wtoken.into(),
+                info.sender.to_string(),
+                amount,
+            )?)
+            .add_attribute("action", "claim")
+            .add_attribute("stage", stage_id.to_string())
+            .add_attribute("to", info.sender)
+            .add_attribute("amount", amount))
+    }
+
+    pub fn clawback(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        stage_id: u64,
+        recipient: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if info.sender != config.owner {
+            return Err(ContractError::Unauthorized);
+        }
+        let mut stage = load_stage(deps.storage, stage_id)?;
+        if !stage.expiration.is_expired(&env.block) {
+            return Err(ContractError::StageNotExpired { stage: stage_id });
+        }
+        let amount = stage.available();
+        if amount.is_zero() {
+            return Err(ContractError::NothingToClawback);
+        }
+        let recipient = match recipient {
+            Some(recipient) => deps.api.addr_validate(&recipient)?,
+            None => config.owner,
+        };
+
+        stage.clawed_back += amount;
+        STAGES.save(deps.storage, stage_id, &stage)?;
+
+        Ok(Response::new()
+            .add_message(wtoken_transfer(
+                config.wtoken.into(),
+                recipient.to_string(),
+                amount,
+            )?)
+            .add_attribute("action", "clawback")
+            .add_attribute("stage", stage_id.to_string())
+            .add_attribute("to", recipient)
+            .add_attribute("amount", amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        from_json,
+        testing::{mock_dependencies, mock_env, mock_info, MockApi},
+        Addr, ContractResult, SubMsg, SystemError, SystemResult, Uint128, WasmMsg, WasmQuery,
+    };
+    use cw20::{Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, MinterResponse};
+    use cw_utils::Expiration;
+
+    use crate::{
+        merkle::{leaf_hash, MerkleTree},
+        msg::{IsClaimedResponse, LatestStageResponse, ReceiveMsg},
+    };
+
+    use super::*;
+
+    fn wtoken() -> Addr {
+        MockApi::default().addr_make("wtoken")
+    }
+
+    fn owner() -> Addr {
+        MockApi::default().addr_make("owner")
+    }
+
+    /// Merkle root and proofs of `entries`, as the `merkle` bin builds them
+    fn airdrop(entries: &[(&Addr, u128)]) -> (String, Vec<Vec<String>>) {
+        let tree = MerkleTree::new(
+            entries
+                .iter()
+                .map(|(address, amount)| leaf_hash(address.as_str(), Uint128::new(*amount)))
+                .collect(),
+        );
+        let proofs = (0..entries.len())
+            .map(|index| tree.proof(index).iter().map(hex::encode).collect())
+            .collect();
+        (hex::encode(tree.root()), proofs)
+    }
+
+    fn do_instantiate(deps: DepsMut) {
+        let msg = InstantiateMsg {
+            owner: Some(owner().into()),
+            wtoken: wtoken().into(),
+        };
+        instantiate(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+    }
+
+    fn register(
+        deps: DepsMut,
+        merkle_root: String,
+        expiration: Option<Expiration>,
+        total_amount: u128,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root,
+            expiration,
+            total_amount: total_amount.into(),
+        };
+        execute(deps, mock_env(), mock_info(owner().as_str(), &[]), msg)
+    }
+
+    fn fund(deps: DepsMut, env: Env, stage: u64, amount: u128) -> Result<Response, ContractError> {
+        fund_from(deps, env, &owner(), stage, amount)
+    }
+
+    fn fund_from(
+        deps: DepsMut,
+        env: Env,
+        sender: &Addr,
+        stage: u64,
+        amount: u128,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.into(),
+            amount: amount.into(),
+            msg: to_json_binary(&ReceiveMsg::Fund { stage }).unwrap(),
+        });
+        execute(deps, env, mock_info(wtoken().as_str(), &[]), msg)
+    }
+
+    fn claim(
+        deps: DepsMut,
+        env: Env,
+        sender: &Addr,
+        stage: u64,
+        amount: u128,
+        proof: &[String],
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Claim {
+            stage,
+            amount: amount.into(),
+            proof: proof.to_vec(),
+        };
+        execute(deps, env, mock_info(sender.as_str(), &[]), msg)
+    }
+
+    fn transfer(recipient: &Addr, amount: u128) -> SubMsg {
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: wtoken().into(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.into(),
+                amount: amount.into(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    }
+
+    fn query_stage(deps: Deps, stage: u64) -> Stage {
+        from_json(query(deps, mock_env(), QueryMsg::Stage { stage }).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn claims_are_proven_against_the_root() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001");
+        let addr2 = deps.api.addr_make("addr0002");
+        let addr3 = deps.api.addr_make("addr0003");
+        let env = mock_env();
+        do_instantiate(deps.as_mut());
+        let (root, proofs) = airdrop(&[(&addr1, 100), (&addr2, 50), (&addr3, 30)]);
+
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: root.clone(),
+            expiration: None,
+            total_amount: 180u128.into(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(addr1.as_str(), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        let err = register(deps.as_mut(), "not a hash".into(), None, 180).unwrap_err();
+        assert_eq!(err, ContractError::InvalidMerkleRoot);
+        register(deps.as_mut(), root, None, 180).unwrap();
+
+        let err = fund(deps.as_mut(), env.clone(), 2, 150).unwrap_err();
+        assert_eq!(err, ContractError::StageNotFound { stage: 2 });
+        fund(deps.as_mut(), env.clone(), 1, 150).unwrap();
+
+        let res = claim(deps.as_mut(), env.clone(), &addr1, 1, 100, &proofs[0]).unwrap();
+        assert_eq!(res.messages, vec![transfer(&addr1, 100)]);
+        let err = claim(deps.as_mut(), env.clone(), &addr1, 1, 100, &proofs[0]).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyClaimed);
+
+        let err = claim(deps.as_mut(), env.clone(), &addr2, 1, 60, &proofs[1]).unwrap_err();
+        assert_eq!(err, ContractError::VerificationFailed);
+        let err = claim(deps.as_mut(), env.clone(), &addr2, 1, 50, &["zz".into()]).unwrap_err();
+        assert_eq!(err, ContractError::InvalidProof);
+        claim(deps.as_mut(), env.clone(), &addr2, 1, 50, &proofs[1]).unwrap();
+
+        // only 150 of the 180 were funded
+        let err = claim(deps.as_mut(), env, &addr3, 1, 30, &proofs[2]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InsufficientStageFunds {
+                stage: 1,
+                available: Uint128::zero()
+            }
+        );
+
+        let stage = query_stage(deps.as_ref(), 1);
+        assert_eq!(stage.claimed, Uint128::new(150));
+        assert_eq!(stage.total_amount, Uint128::new(180));
+        let msg = QueryMsg::IsClaimed {
+            stage: 1,
+            address: addr2.to_string(),
+        };
+        let res: IsClaimedResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(res.is_claimed);
+    }
+
+    #[test]
+    fn expired_stages_are_clawed_back() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001");
+        let addr2 = deps.api.addr_make("addr0002");
+        let treasury = deps.api.addr_make("treasury");
+        let mut env = mock_env();
+        do_instantiate(deps.as_mut());
+        let (root, proofs) = airdrop(&[(&addr1, 40), (&addr2, 60)]);
+
+        let expiration = Expiration::AtHeight(env.block.height + 10);
+        register(deps.as_mut(), root.clone(), Some(expiration), 100).unwrap();
+        fund(deps.as_mut(), env.clone(), 1, 100).unwrap();
+        claim(deps.as_mut(), env.clone(), &addr1, 1, 40, &proofs[0]).unwrap();
+
+        let msg = ExecuteMsg::Clawback {
+            stage: 1,
+            recipient: Some(treasury.to_string()),
+        };
+        let owner_info = mock_info(owner().as_str(), &[]);
+        let err = execute(deps.as_mut(), env.clone(), owner_info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::StageNotExpired { stage: 1 });
+
+        env.block.height += 10;
+        let err = claim(deps.as_mut(), env.clone(), &addr2, 1, 60, &proofs[1]).unwrap_err();
+        assert_eq!(err, ContractError::StageExpired { stage: 1 });
+        let err = fund(deps.as_mut(), env.clone(), 1, 10).unwrap_err();
+        assert_eq!(err, ContractError::StageExpired { stage: 1 });
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(addr2.as_str(), &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        let res = execute(deps.as_mut(), env.clone(), owner_info.clone(), msg.clone()).unwrap();
+        assert_eq!(res.messages, vec![transfer(&treasury, 60)]);
+        let err = execute(deps.as_mut(), env.clone(), owner_info, msg).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClawback);
+
+        // the next stage has its own claims
+        register(deps.as_mut(), root, None, 100).unwrap();
+        let res: LatestStageResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::LatestStage {}).unwrap()).unwrap();
+        assert_eq!(res.latest_stage, 2);
+        fund(deps.as_mut(), env.clone(), 2, 100).unwrap();
+        claim(deps.as_mut(), env, &addr1, 2, 40, &proofs[0]).unwrap();
+        assert_eq!(query_stage(deps.as_ref(), 2).available(), Uint128::new(60));
+    }
+
+    #[test]
+    fn funding_is_capped_and_restricted() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001");
+        let minter = deps.api.addr_make("minter");
+        let env = mock_env();
+        do_instantiate(deps.as_mut());
+        let (root, _) = airdrop(&[(&addr1, 100)]);
+        register(deps.as_mut(), root, None, 100).unwrap();
+
+        let wtoken_minter = minter.to_string();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if *contract_addr == wtoken().as_str() => {
+                let Cw20QueryMsg::Minter {} = from_json(msg).unwrap() else {
+                    panic!("unexpected query");
+                };
+                let res = Some(MinterResponse {
+                    minter: wtoken_minter.clone(),
+                    cap: None,
+                });
+                SystemResult::Ok(ContractResult::Ok(to_json_binary(&res).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "only the wtoken minter is mocked".to_string(),
+            }),
+        });
+
+        // any holder could send Wtoken, only the owner and the minter fund stages
+        let err = fund_from(deps.as_mut(), env.clone(), &addr1, 1, 50).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+
+        fund_from(deps.as_mut(), env.clone(), &minter, 1, 70).unwrap();
+        let res = fund(deps.as_mut(), env.clone(), 1, 50).unwrap();
+        assert_eq!(res.messages, vec![transfer(&owner(), 20)]);
+        assert_eq!(query_stage(deps.as_ref(), 1).funded, Uint128::new(100));
+
+        let err = fund_from(deps.as_mut(), env, &minter, 1, 1).unwrap_err();
+        assert_eq!(err, ContractError::StageFullyFunded { stage: 1 });
+    }
+
+    /// Registers and fully funds stage 1 for `entries`, returns the proofs
+    fn funded_stage(
+        mut deps: DepsMut,
+        entries: &[(&Addr, u128)],
+        expiration: Option<Expiration>,
+    ) -> Vec<Vec<String>> {
+        let (root, proofs) = airdrop(entries);
+        let total = entries.iter().map(|(_, amount)| amount).sum();
+        register(deps.branch(), root, expiration, total).unwrap();
+        fund(deps, mock_env(), 1, total).unwrap();
+        proofs
+    }
+
+    #[test]
+    fn claim_with_invalid_proof_fails() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001");
+        let addr2 = deps.api.addr_make("addr0002");
+        let addr3 = deps.api.addr_make("addr0003");
+        let env = mock_env();
+        do_instantiate(deps.as_mut());
+        let proofs = funded_stage(deps.as_mut(), &[(&addr1, 100), (&addr2, 50)], None);
+        let (_, other_proofs) = airdrop(&[(&addr1, 100), (&addr3, 50)]);
+
+        // someone else's proof, a proof of another tree, or no proof at all
+        for (sender, proof) in [
+            (&addr1, &proofs[1]),
+            (&addr1, &other_proofs[0]),
+            (&addr3, &proofs[1]),
+            (&addr1, &vec![]),
+        ] {
+            let err = claim(deps.as_mut(), env.clone(), sender, 1, 100, proof).unwrap_err();
+            assert_eq!(err, ContractError::VerificationFailed);
+        }
+        let truncated = vec![proofs[0][0][..62].to_string()];
+        let err = claim(deps.as_mut(), env, &addr1, 1, 100, &truncated).unwrap_err();
+        assert_eq!(err, ContractError::InvalidProof);
+
+        assert_eq!(query_stage(deps.as_ref(), 1).claimed, Uint128::zero());
+    }
+
+    #[test]
+    fn claim_twice_fails() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001");
+        let addr2 = deps.api.addr_make("addr0002");
+        let env = mock_env();
+        do_instantiate(deps.as_mut());
+        let proofs = funded_stage(deps.as_mut(), &[(&addr1, 100), (&addr2, 50)], None);
+
+        claim(deps.as_mut(), env.clone(), &addr1, 1, 100, &proofs[0]).unwrap();
+        let err = claim(deps.as_mut(), env.clone(), &addr1, 1, 100, &proofs[0]).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyClaimed);
+        // another amount doesn't help either
+        let err = claim(deps.as_mut(), env, &addr1, 1, 50, &proofs[1]).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyClaimed);
+
+        let stage = query_stage(deps.as_ref(), 1);
+        assert_eq!(stage.claimed, Uint128::new(100));
+        assert_eq!(stage.available(), Uint128::new(50));
+    }
+
+    #[test]
+    fn claim_after_expiry_fails() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001");
+        let mut env = mock_env();
+        do_instantiate(deps.as_mut());
+        let expiration = Expiration::AtTime(env.block.time.plus_seconds(3600));
+        let proofs = funded_stage(deps.as_mut(), &[(&addr1, 100)], Some(expiration));
+
+        env.block.time = env.block.time.plus_seconds(3600);
+        let err = claim(deps.as_mut(), env.clone(), &addr1, 1, 100, &proofs[0]).unwrap_err();
+        assert_eq!(err, ContractError::StageExpired { stage: 1 });
+
+        let msg = QueryMsg::IsClaimed {
+            stage: 1,
+            address: addr1.to_string(),
+        };
+        let res: IsClaimedResponse = from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert!(!res.is_claimed);
+    }
+
+    #[test]
+    fn clawback_returns_what_was_not_claimed() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001");
+        let addr2 = deps.api.addr_make("addr0002");
+        let mut env = mock_env();
+        do_instantiate(deps.as_mut());
+        let expiration = Expiration::AtHeight(env.block.height + 100);
+        let proofs = funded_stage(
+            deps.as_mut(),
+            &[(&addr1, 100), (&addr2, 50)],
+            Some(expiration),
+        );
+        claim(deps.as_mut(), env.clone(), &addr2, 1, 50, &proofs[1]).unwrap();
+
+        let msg = ExecuteMsg::Clawback {
+            stage: 1,
+            recipient: None,
+        };
+        let owner_info = mock_info(owner().as_str(), &[]);
+        let err = execute(deps.as_mut(), env.clone(), owner_info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::StageNotExpired { stage: 1 });
+
+        // the owner gets the rest when no recipient is given
+        env.block.height += 100;
+        let res = execute(deps.as_mut(), env.clone(), owner_info.clone(), msg.clone()).unwrap();
+        assert_eq!(res.messages, vec![transfer(&owner(), 100)]);
+        let stage = query_stage(deps.as_ref(), 1);
+        assert_eq!(stage.clawed_back, Uint128::new(100));
+        assert_eq!(stage.available(), Uint128::zero());
+
+        let err = execute(deps.as_mut(), env.clone(), owner_info, msg).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClawback);
+        let msg = ExecuteMsg::Clawback {
+            stage: 2,
+            recipient: None,
+        };
+        let err = execute(deps.as_mut(), env, mock_info(owner().as_str(), &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::StageNotFound { stage: 2 });
+    }
+}
@@ -0,0 +1,38 @@
+use cosmwasm_std::{Addr, OverflowError, StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    StdError(#[from] StdError),
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+    #[error("Unauthorized")]
+    Unauthorized,
+    #[error("{address} is not wtoken address")]
+    InvalidWtokenAddress { address: Addr },
+    #[error("zero amount received")]
+    ZeroAmountReceived,
+    #[error("Merkle root must be a hex encoded sha256 hash")]
+    InvalidMerkleRoot,
+    #[error("Proof elements must be hex encoded sha256 hashes")]
+    InvalidProof,
+    #[error("Proof doesn't match the merkle root of the stage")]
+    VerificationFailed,
+    #[error("Stage {stage} not found")]
+    StageNotFound { stage: u64 },
+    #[error("Stage {stage} expired")]
+    StageExpired { stage: u64 },
+    #[error("Stage {stage} has not expired yet")]
+    StageNotExpired { stage: u64 },
+    #[error("Already claimed")]
+    AlreadyClaimed,
+    #[error("Stage {stage} only has {available} left")]
+    InsufficientStageFunds { stage: u64, available: Uint128 },
+    #[error("Stage {stage} is already fully funded")]
+    StageFullyFunded { stage: u64 },
+    #[error("Nothing to claw back")]
+    NothingToClawback,
+}
+
+pub type ContractResult<T> = Result<T, ContractError>;
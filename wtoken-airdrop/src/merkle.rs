@@ -0,0 +1,113 @@
+//! Merkle trees of airdrop stages. Leaves are `sha256(0x00 || "<address>:<amount>")`
+//! and inner nodes `sha256(0x01 || min(a, b) || max(a, b))`: the prefixes keep a
+//! pair of nodes from passing as a leaf, and sorting the pair means proofs don't
+//! need to say on which side a node is.
+
+use cosmwasm_std::Uint128;
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+pub fn leaf_hash(address: &str, amount: Uint128) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(format!("{address}:{amount}"));
+    hasher.finalize().into()
+}
+
+fn hash_pair(a: &Hash, b: &Hash) -> Hash {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().into()
+}
+
+pub fn verify(leaf: Hash, proof: &[Hash], root: &Hash) -> bool {
+    proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(&node, sibling))
+        == *root
+}
+
+/// A node without sibling moves up a layer unchanged
+pub struct MerkleTree {
+    layers: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    /// `leaves` must not be empty
+    pub fn new(leaves: Vec<Hash>) -> Self {
+        assert!(!leaves.is_empty(), "merkle tree needs at least one leaf");
+        let mut layers = vec![leaves];
+        while let Some(layer) = layers.last().filter(|layer| layer.len() > 1) {
+            let next = layer
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+        Self { layers }
+    }
+
+    pub fn root(&self) -> Hash {
+        self.layers[self.layers.len() - 1][0]
+    }
+
+    /// Proof of the leaf at `index`
+    pub fn proof(&self, mut index: usize) -> Vec<Hash> {
+        let mut proof = vec![];
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        proof
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proofs_verify_for_every_leaf() {
+        for size in 1..=9u128 {
+            let leaves: Vec<_> = (0..size)
+                .map(|i| leaf_hash(&format!("addr{i}"), Uint128::new(i)))
+                .collect();
+            let tree = MerkleTree::new(leaves.clone());
+            for (index, leaf) in leaves.iter().enumerate() {
+                assert!(verify(*leaf, &tree.proof(index), &tree.root()));
+            }
+            let wrong_amount = leaf_hash("addr0", Uint128::new(1));
+            assert!(!verify(wrong_amount, &tree.proof(0), &tree.root()));
+        }
+    }
+
+    #[test]
+    fn leaves_are_separated() {
+        // without a delimiter both would hash "addr112"
+        assert_ne!(
+            leaf_hash("addr1", Uint128::new(12)),
+            leaf_hash("addr11", Uint128::new(2))
+        );
+
+        // inner nodes are hashed with their own prefix, unlike leaves
+        let (a, b) = (
+            leaf_hash("addr0", Uint128::new(1)),
+            leaf_hash("addr1", Uint128::new(2)),
+        );
+        let unprefixed: Hash = Sha256::digest([a.min(b), a.max(b)].concat()).into();
+        assert_ne!(hash_pair(&a, &b), unprefixed);
+    }
+}
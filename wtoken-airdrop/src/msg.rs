@@ -0,0 +1,68 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::Uint128;
+use cw20::Cw20ReceiveMsg;
+use cw_utils::Expiration;
+
+use crate::state::{Config, Stage};
+
+/// The sender becomes owner unless `owner` is set
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub owner: Option<String>,
+    pub wtoken: String,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Wtoken funding a stage, with a `ReceiveMsg` payload. Only accepted from the owner
+    /// or the Wtoken minter, anything above the stage's `total_amount` is refunded.
+    Receive(Cw20ReceiveMsg),
+    /// Only callable by the owner, adds the next stage. `None` never expires.
+    RegisterMerkleRoot {
+        merkle_root: String,
+        expiration: Option<Expiration>,
+        total_amount: Uint128,
+    },
+    /// Sends `amount` to the sender if `proof` links it to the merkle root of `stage`
+    Claim {
+        stage: u64,
+        amount: Uint128,
+        /// Hex encoded sibling hashes, leaf first
+        proof: Vec<String>,
+    },
+    /// Only callable by the owner once `stage` expired, sends what wasn't claimed
+    /// to `recipient`, or the owner
+    Clawback {
+        stage: u64,
+        recipient: Option<String>,
+    },
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    Fund { stage: u64 },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(Config)]
+    Config {},
+    #[returns(Stage)]
+    Stage { stage: u64 },
+    #[returns(LatestStageResponse)]
+    LatestStage {},
+    #[returns(IsClaimedResponse)]
+    IsClaimed { stage: u64, address: String },
+}
+
+#[cw_serde]
+pub struct LatestStageResponse {
+    /// `0` before the first stage is registered
+    pub latest_stage: u64,
+}
+
+#[cw_serde]
+pub struct IsClaimedResponse {
+    pub is_claimed: bool,
+}
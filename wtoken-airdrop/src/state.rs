@@ -0,0 +1,36 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Empty, Uint128};
+use cw_storage_plus::{Item, Map};
+use cw_utils::Expiration;
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const LATEST_STAGE: Item<u64> = Item::new("latest_stage");
+pub const STAGES: Map<u64, Stage> = Map::new("stages");
+/// (stage, address) of the claims made
+pub const CLAIMS: Map<(u64, &Addr), Empty> = Map::new("claims");
+
+#[cw_serde]
+pub struct Config {
+    /// Registers stages and claws back what expired stages didn't pay out
+    pub owner: Addr,
+    pub wtoken: Addr,
+}
+
+#[cw_serde]
+pub struct Stage {
+    /// Hex encoded, see `merkle`
+    pub merkle_root: String,
+    pub expiration: Expiration,
+    /// Sum of the amounts in the tree, the stage needs this much funding
+    pub total_amount: Uint128,
+    pub funded: Uint128,
+    pub claimed: Uint128,
+    pub clawed_back: Uint128,
+}
+
+impl Stage {
+    /// Wtoken of the stage that is still in the contract
+    pub fn available(&self) -> Uint128 {
+        self.funded - self.claimed - self.clawed_back
+    }
+}
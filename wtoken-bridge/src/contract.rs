@@ -0,0 +1,490 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult,
+    SubMsgResult,
+};
+
+use crate::{
+    error::ContractError,
+    ibc::{ack_fail, RECEIVE_REPLY_ID, REFUND_REPLY_ID},
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{
+        undo_reduce_channel_balance, Config, ReceiveReplyArgs, RefundReplyArgs, CONFIG,
+        PENDING_REFUNDS, RECEIVE_REPLY_ARGS, REFUND_REPLY_ARGS,
+    },
+};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let InstantiateMsg {
+        admin,
+        wtoken,
+        default_timeout,
+    } = msg;
+    if default_timeout == 0 {
+        return Err(ContractError::InvalidTimeout);
+    }
+    let admin = match admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender,
+    };
+    let config = Config {
+        admin,
+        wtoken: deps.api.addr_validate(&wtoken)?,
+        default_timeout,
+    };
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "instantiate")
+        .add_attribute("admin", config.admin)
+        .add_attribute("wtoken", config.wtoken))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    use ExecuteMsg::*;
+    match msg {
+        Receive(receive_msg) => exec::receive(deps, env, info, receive_msg),
+        AllowChannel { channel } => exec::update_channel(deps, info, channel, true),
+        DisallowChannel { channel } => exec::update_channel(deps, info, channel, false),
+        RetryRefund { sender } => exec::retry_refund(deps, sender),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        // Only sent on error: the escrow goes back and the packet gets a failure ack
+        RECEIVE_REPLY_ID => match msg.result {
+            SubMsgResult::Ok(_) => Ok(Response::new()),
+            SubMsgResult::Err(err) => {
+                let ReceiveReplyArgs { channel, amount } = RECEIVE_REPLY_ARGS.load(deps.storage)?;
+                undo_reduce_channel_balance(deps.storage, &channel, amount)?;
+                Ok(Response::new().set_data(ack_fail(err)))
+            }
+        },
+        // Only sent on error: the Wtoken stays here until `RetryRefund`
+        REFUND_REPLY_ID => match msg.result {
+            SubMsgResult::Ok(_) => Ok(Response::new()),
+            SubMsgResult::Err(err) => {
+                let RefundReplyArgs { sender, amount } = REFUND_REPLY_ARGS.load(deps.storage)?;
+                PENDING_REFUNDS.update(deps.storage, &sender, |pending| -> StdResult<_> {
+                    Ok(pending.unwrap_or_default() + amount)
+                })?;
+                Ok(Response::new()
+                    .add_attribute("action", "refund_failed")
+                    .add_attribute("sender", sender)
+                    .add_attribute("amount", amount)
+                    .add_attribute("error", err))
+            }
+        },
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    use QueryMsg::*;
+    Ok(match msg {
+        Config {} => to_json_binary(&CONFIG.load(deps.storage)?)?,
+        Channel { id } => to_json_binary(&query::channel(deps, id)?)?,
+        ListChannels {} => to_json_binary(&query::list_channels(deps)?)?,
+        PendingRefund { sender } => to_json_binary(&query::pending_refund(deps, sender)?)?,
+    })
+}
+
+mod query {
+    use cosmwasm_std::Order;
+
+    use crate::{
+        msg::{ChannelResponse, ListChannelsResponse, PendingRefundResponse},
+        state::{ChannelInfo, ALLOWED_CHANNELS, CHANNEL_INFO, CHANNEL_STATE},
+    };
+
+    use super::*;
+
+    pub fn channel(deps: Deps, id: String) -> StdResult<ChannelResponse> {
+        let info = CHANNEL_INFO.load(deps.storage, &id)?;
+        channel_response(deps, info)
+    }
+
+    pub fn list_channels(deps: Deps) -> StdResult<ListChannelsResponse> {
+        let channels = CHANNEL_INFO
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| channel_response(deps, item?.1))
+            .collect::<StdResult<_>>()?;
+        Ok(ListChannelsResponse { channels })
+    }
+
+    pub fn pending_refund(deps: Deps, sender: String) -> StdResult<PendingRefundResponse> {
+        let amount = PENDING_REFUNDS
+            .may_load(deps.storage, &sender)?
+            .unwrap_or_default();
+        Ok(PendingRefundResponse { amount })
+    }
+
+    fn channel_response(deps: Deps, info: ChannelInfo) -> StdResult<ChannelResponse> {
+        let state = CHANNEL_STATE
+            .may_load(deps.storage, &info.id)?
+            .unwrap_or_default();
+        Ok(ChannelResponse {
+            allowed: ALLOWED_CHANNELS.has(deps.storage, &info.id),
+            outstanding: state.outstanding,
+            total_sent: state.total_sent,
+            info,
+        })
+    }
+}
+
+mod exec {
+    use cosmwasm_std::{from_json, Empty, IbcMsg, IbcTimeout, WasmMsg};
+    use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+
+    use crate::{
+        ibc::{wtoken_denom, Ics20Packet},
+        msg::TransferMsg,
+        state::{increase_channel_balance, ALLOWED_CHANNELS, CHANNEL_INFO},
+    };
+
+    use super::*;
+
+    /// Escrows the received Wtoken and sends it over IBC
+    pub fn receive(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        wrapper: Cw20ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if info.sender != config.wtoken {
+            return Err(ContractError::InvalidWtokenAddress {
+                address: info.sender,
+            });
+        }
+        if wrapper.amount.is_zero() {
+            return Err(ContractError::ZeroAmountReceived);
+        }
+
+        let TransferMsg {
+            channel,
+            remote_address,
+            timeout,
+            memo,
+        } = from_json(&wrapper.msg)?;
+        if !CHANNEL_INFO.has(deps.storage, &channel) {
+            return Err(ContractError::UnknownChannel { channel });
+        }
+        if !ALLOWED_CHANNELS.has(deps.storage, &channel) {
+            return Err(ContractError::ChannelNotAllowed { channel });
+        }
+        let timeout = timeout.unwrap_or(config.default_timeout);
+        if timeout == 0 {
+            return Err(ContractError::InvalidTimeout);
+        }
+
+        increase_channel_balance(deps.storage, &channel, wrapper.amount)?;
+
+        let packet = Ics20Packet {
+            amount: wrapper.amount,
+            denom: wtoken_denom(&config.wtoken),
+            receiver: remote_address,
+            sender: wrapper.sender,
+            memo,
+        };
+        let res = Response::new()
+            .add_attribute("action", "transfer")
+            .add_attribute("sender", &packet.sender)
+            .add_attribute("receiver", &packet.receiver)
+            .add_attribute("denom", &packet.denom)
+            .add_attribute("amount", packet.amount)
+            .add_attribute("channel", &channel);
+        let send_packet = IbcMsg::SendPacket {
+            channel_id: channel,
+            data: to_json_binary(&packet)?,
+            timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(timeout)),
+        };
+        Ok(res.add_message(send_packet))
+    }
+
+    /// Transfers need the channel to be allowed here and opened through the handshake
+    pub fn update_channel(
+        deps: DepsMut,
+        info: MessageInfo,
+        channel: String,
+        allowed: bool,
+    ) -> Result<Response, ContractError> {
+        if info.sender != CONFIG.load(deps.storage)?.admin {
+            return Err(ContractError::Unauthorized);
+        }
+        if allowed {
+            ALLOWED_CHANNELS.save(deps.storage, &channel, &Empty {})?;
+        } else {
+            ALLOWED_CHANNELS.remove(deps.storage, &channel);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "update_channel")
+            .add_attribute("channel", channel)
+            .add_attribute("allowed", allowed.to_string()))
+    }
+
+    /// Sends a refund that failed earlier again. If it fails again the whole call
+    /// reverts and the refund stays pending.
+    pub fn retry_refund(deps: DepsMut, sender: String) -> Result<Response, ContractError> {
+        let amount = PENDING_REFUNDS
+            .may_load(deps.storage, &sender)?
+            .ok_or_else(|| ContractError::NoPendingRefund {
+                sender: sender.clone(),
+            })?;
+        PENDING_REFUNDS.remove(deps.storage, &sender);
+
+        let refund = WasmMsg::Execute {
+            contract_addr: CONFIG.load(deps.storage)?.wtoken.into(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: sender.clone(),
+                amount,
+            })?,
+            funds: vec![],
+        };
+
+        Ok(Response::new()
+            .add_message(refund)
+            .add_attribute("action", "retry_refund")
+            .add_attribute("sender", sender)
+            .add_attribute("amount", amount))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use cosmwasm_std::{
+        from_json,
+        testing::{mock_dependencies, mock_env, mock_ibc_channel_connect_ack, mock_info, MockApi},
+        Addr, CosmosMsg, IbcMsg, IbcTimeout, SubMsg, Uint128,
+    };
+    use cw20::Cw20ReceiveMsg;
+
+    use crate::{
+        ibc::{ibc_channel_connect, wtoken_denom, Ics20Packet, ICS20_ORDERING, ICS20_VERSION},
+        msg::{ChannelResponse, TransferMsg},
+    };
+
+    use super::*;
+
+    pub const CHANNEL: &str = "channel-1";
+    pub const DEFAULT_TIMEOUT: u64 = 3600;
+    pub const REMOTE_ADDRESS: &str = "osmo1remote";
+
+    pub fn wtoken() -> Addr {
+        MockApi::default().addr_make("wtoken")
+    }
+
+    pub fn admin() -> Addr {
+        MockApi::default().addr_make("admin")
+    }
+
+    pub fn connect_channel(deps: DepsMut, channel: &str) {
+        let msg = mock_ibc_channel_connect_ack(channel, ICS20_ORDERING, ICS20_VERSION);
+        ibc_channel_connect(deps, mock_env(), msg).unwrap();
+    }
+
+    /// Instantiates the bridge with `CHANNEL` open and allowed
+    pub fn setup(mut deps: DepsMut) {
+        let msg = InstantiateMsg {
+            admin: Some(admin().into()),
+            wtoken: wtoken().into(),
+            default_timeout: DEFAULT_TIMEOUT,
+        };
+        instantiate(deps.branch(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        connect_channel(deps.branch(), CHANNEL);
+        let msg = ExecuteMsg::AllowChannel {
+            channel: CHANNEL.into(),
+        };
+        execute(deps, mock_env(), mock_info(admin().as_str(), &[]), msg).unwrap();
+    }
+
+    pub fn transfer_msg(channel: &str, timeout: Option<u64>) -> TransferMsg {
+        TransferMsg {
+            channel: channel.into(),
+            remote_address: REMOTE_ADDRESS.into(),
+            timeout,
+            memo: None,
+        }
+    }
+
+    /// Sends `amount` Wtoken of `sender` to the bridge with `msg` as payload
+    pub fn send_wtoken(
+        deps: DepsMut,
+        sender: &str,
+        amount: u128,
+        msg: &TransferMsg,
+    ) -> Result<Response, ContractError> {
+        let receive_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.into(),
+            amount: amount.into(),
+            msg: to_json_binary(msg).unwrap(),
+        });
+        execute(
+            deps,
+            mock_env(),
+            mock_info(wtoken().as_str(), &[]),
+            receive_msg,
+        )
+    }
+
+    pub fn query_channel(deps: Deps, id: &str) -> ChannelResponse {
+        let msg = QueryMsg::Channel { id: id.into() };
+        from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn transfer_sends_ics20_packet() {
+        let mut deps = mock_dependencies();
+        let sender = deps.api.addr_make("addr0001").to_string();
+        setup(deps.as_mut());
+
+        let msg = TransferMsg {
+            memo: Some("hello".into()),
+            ..transfer_msg(CHANNEL, None)
+        };
+        let res = send_wtoken(deps.as_mut(), &sender, 100, &msg).unwrap();
+        let packet = Ics20Packet {
+            amount: Uint128::new(100),
+            denom: wtoken_denom(&wtoken()),
+            receiver: REMOTE_ADDRESS.into(),
+            sender: sender.clone(),
+            memo: Some("hello".into()),
+        };
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(IbcMsg::SendPacket {
+                channel_id: CHANNEL.into(),
+                data: to_json_binary(&packet).unwrap(),
+                timeout: IbcTimeout::with_timestamp(
+                    mock_env().block.time.plus_seconds(DEFAULT_TIMEOUT)
+                ),
+            })]
+        );
+
+        // the sender can pick a shorter timeout
+        let res =
+            send_wtoken(deps.as_mut(), &sender, 50, &transfer_msg(CHANNEL, Some(60))).unwrap();
+        let CosmosMsg::Ibc(IbcMsg::SendPacket { timeout, .. }) = res.messages[0].msg.clone() else {
+            panic!("expected a packet");
+        };
+        assert_eq!(
+            timeout,
+            IbcTimeout::with_timestamp(mock_env().block.time.plus_seconds(60))
+        );
+
+        let channel = query_channel(deps.as_ref(), CHANNEL);
+        assert!(channel.allowed);
+        assert_eq!(channel.outstanding, Uint128::new(150));
+        assert_eq!(channel.total_sent, Uint128::new(150));
+    }
+
+    #[test]
+    fn transfer_checks_token_and_channel() {
+        let mut deps = mock_dependencies();
+        let sender = deps.api.addr_make("addr0001").to_string();
+        let other_token = deps.api.addr_make("other_token");
+        setup(deps.as_mut());
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.clone(),
+            amount: 100u128.into(),
+            msg: to_json_binary(&transfer_msg(CHANNEL, None)).unwrap(),
+        });
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(other_token.as_str(), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidWtokenAddress {
+                address: other_token
+            }
+        );
+
+        let err = send_wtoken(deps.as_mut(), &sender, 0, &transfer_msg(CHANNEL, None)).unwrap_err();
+        assert_eq!(err, ContractError::ZeroAmountReceived);
+
+        let err =
+            send_wtoken(deps.as_mut(), &sender, 100, &transfer_msg(CHANNEL, Some(0))).unwrap_err();
+        assert_eq!(err, ContractError::InvalidTimeout);
+
+        let err = send_wtoken(
+            deps.as_mut(),
+            &sender,
+            100,
+            &transfer_msg("channel-2", None),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::UnknownChannel {
+                channel: "channel-2".into()
+            }
+        );
+
+        // open but not allowed yet
+        connect_channel(deps.as_mut(), "channel-2");
+        let err = send_wtoken(
+            deps.as_mut(),
+            &sender,
+            100,
+            &transfer_msg("channel-2", None),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ChannelNotAllowed {
+                channel: "channel-2".into()
+            }
+        );
+
+        let msg = ExecuteMsg::AllowChannel {
+            channel: "channel-2".into(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&sender, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+
+        let msg = ExecuteMsg::DisallowChannel {
+            channel: CHANNEL.into(),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(admin().as_str(), &[]),
+            msg,
+        )
+        .unwrap();
+        let err =
+            send_wtoken(deps.as_mut(), &sender, 100, &transfer_msg(CHANNEL, None)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ChannelNotAllowed {
+                channel: CHANNEL.into()
+            }
+        );
+        assert_eq!(
+            query_channel(deps.as_ref(), CHANNEL).outstanding,
+            Uint128::zero()
+        );
+    }
+}
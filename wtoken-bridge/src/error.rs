@@ -0,0 +1,38 @@
+use cosmwasm_std::{Addr, OverflowError, StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    StdError(#[from] StdError),
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+    #[error("Unauthorized")]
+    Unauthorized,
+    #[error("{address} is not wtoken address")]
+    InvalidWtokenAddress { address: Addr },
+    #[error("zero amount received")]
+    ZeroAmountReceived,
+    #[error("Channel {channel} is not an open ICS-20 channel")]
+    UnknownChannel { channel: String },
+    #[error("Transfers over channel {channel} are not allowed")]
+    ChannelNotAllowed { channel: String },
+    #[error("Timeout must be greater than zero")]
+    InvalidTimeout,
+    #[error("Only supports channel with ibc version {expected}, got {version}")]
+    InvalidIbcVersion { expected: String, version: String },
+    #[error("Only supports unordered channels")]
+    OnlyUnorderedChannel,
+    #[error("ICS-20 channels may not be closed")]
+    CannotClose,
+    #[error("Only wtoken can be received back, got {denom}")]
+    InvalidDenom { denom: String },
+    #[error("Channel {channel} only has {escrowed} escrowed")]
+    InsufficientEscrow { channel: String, escrowed: Uint128 },
+    #[error("No pending refund for {sender}")]
+    NoPendingRefund { sender: String },
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+}
+
+pub type ContractResult<T> = Result<T, ContractError>;
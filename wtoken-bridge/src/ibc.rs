@@ -0,0 +1,512 @@
+use cosmwasm_schema::cw_serde;
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, Binary, DepsMut, Env, IbcBasicResponse, IbcChannel,
+    IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcChannelOpenResponse, IbcOrder,
+    IbcPacket, IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse,
+    SubMsg, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+
+use crate::{
+    error::ContractError,
+    state::{
+        reduce_channel_balance, undo_increase_channel_balance, ChannelInfo, ReceiveReplyArgs,
+        RefundReplyArgs, CHANNEL_INFO, CONFIG, RECEIVE_REPLY_ARGS, REFUND_REPLY_ARGS,
+    },
+};
+
+pub const ICS20_VERSION: &str = "ics20-1";
+pub const ICS20_ORDERING: IbcOrder = IbcOrder::Unordered;
+
+/// Payout of a received packet, replies on error only
+pub const RECEIVE_REPLY_ID: u64 = 1;
+/// Refund of a failed or timed out packet, replies on error only
+pub const REFUND_REPLY_ID: u64 = 2;
+
+/// Packet data of ICS-20 fungible token transfers
+#[cw_serde]
+pub struct Ics20Packet {
+    pub amount: Uint128,
+    pub denom: String,
+    pub receiver: String,
+    pub sender: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// ICS-20 acknowledgement, `{"result": "AQ=="}` on success
+#[cw_serde]
+pub enum Ics20Ack {
+    Result(Binary),
+    Error(String),
+}
+
+/// Denom of Wtoken in the packets sent by this contract
+pub fn wtoken_denom(wtoken: &Addr) -> String {
+    format!("cw20:{wtoken}")
+}
+
+fn ack_success() -> Binary {
+    to_json_binary(&Ics20Ack::Result(Binary::from([1]))).unwrap()
+}
+
+pub fn ack_fail(err: String) -> Binary {
+    to_json_binary(&Ics20Ack::Error(err)).unwrap()
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_open(
+    _deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    enforce_order_and_version(msg.channel(), msg.counterparty_version())?;
+    Ok(None)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    enforce_order_and_version(channel, msg.counterparty_version())?;
+
+    let info = ChannelInfo {
+        id: channel.endpoint.channel_id.clone(),
+        counterparty_endpoint: channel.counterparty_endpoint.clone(),
+        connection_id: channel.connection_id.clone(),
+    };
+    CHANNEL_INFO.save(deps.storage, &info.id, &info)?;
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("action", "channel_connect")
+        .add_attribute("channel", info.id))
+}
+
+fn enforce_order_and_version(
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), ContractError> {
+    for version in [Some(channel.version.as_str()), counterparty_version]
+        .into_iter()
+        .flatten()
+    {
+        if version != ICS20_VERSION {
+            return Err(ContractError::InvalidIbcVersion {
+                expected: ICS20_VERSION.into(),
+                version: version.into(),
+            });
+        }
+    }
+    if channel.order != ICS20_ORDERING {
+        return Err(ContractError::OnlyUnorderedChannel);
+    }
+    Ok(())
+}
+
+/// Closing would strand the escrowed Wtoken
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_close(
+    _deps: DepsMut,
+    _env: Env,
+    _msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    Err(ContractError::CannotClose)
+}
+
+/// Pays out Wtoken coming back over a channel. Errors become failure acks,
+/// so the sending chain refunds its sender.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, ContractError> {
+    do_ibc_packet_receive(deps, &msg.packet).or_else(|err| {
+        Ok(IbcReceiveResponse::new(ack_fail(err.to_string()))
+            .add_attribute("action", "receive")
+            .add_attribute("success", "false")
+            .add_attribute("error", err.to_string()))
+    })
+}
+
+// Failure acks keep state changes, so the channel balance has to be the last write.
+// The reply args written before it are overwritten by the next packet.
+fn do_ibc_packet_receive(
+    deps: DepsMut,
+    packet: &IbcPacket,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let msg: Ics20Packet = from_json(&packet.data)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    // Tokens returning to this chain are prefixed with the endpoint that sends them back
+    let expected_denom = format!(
+        "{}/{}/{}",
+        packet.src.port_id,
+        packet.src.channel_id,
+        wtoken_denom(&config.wtoken)
+    );
+    if msg.denom != expected_denom {
+        return Err(ContractError::InvalidDenom { denom: msg.denom });
+    }
+
+    let channel = packet.dest.channel_id.clone();
+    let payout = WasmMsg::Execute {
+        contract_addr: config.wtoken.into(),
+        msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: msg.receiver.clone(),
+            amount: msg.amount,
+        })?,
+        funds: vec![],
+    };
+    RECEIVE_REPLY_ARGS.save(
+        deps.storage,
+        &ReceiveReplyArgs {
+            channel: channel.clone(),
+            amount: msg.amount,
+        },
+    )?;
+    reduce_channel_balance(deps.storage, &channel, msg.amount)?;
+
+    Ok(IbcReceiveResponse::new(ack_success())
+        .add_submessage(SubMsg::reply_on_error(payout, RECEIVE_REPLY_ID))
+        .add_attribute("action", "receive")
+        .add_attribute("sender", msg.sender)
+        .add_attribute("receiver", msg.receiver)
+        .add_attribute("amount", msg.amount)
+        .add_attribute("channel", channel)
+        .add_attribute("success", "true"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_ack(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let packet = msg.original_packet;
+    match from_json(&msg.acknowledgement.data)? {
+        Ics20Ack::Result(_) => {
+            let msg: Ics20Packet = from_json(&packet.data)?;
+            Ok(IbcBasicResponse::new()
+                .add_attribute("action", "acknowledge")
+                .add_attribute("sender", msg.sender)
+                .add_attribute("receiver", msg.receiver)
+                .add_attribute("amount", msg.amount)
+                .add_attribute("success", "true"))
+        }
+        Ics20Ack::Error(err) => refund(deps, "acknowledge", &packet, err),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_timeout(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    refund(deps, "timeout", &msg.packet, "timeout".into())
+}
+
+/// Releases the escrow of a packet that failed on the other chain and returns it to its sender.
+/// A refund that fails is kept in `PENDING_REFUNDS` instead of failing the packet.
+fn refund(
+    deps: DepsMut,
+    action: &str,
+    packet: &IbcPacket,
+    err: String,
+) -> Result<IbcBasicResponse, ContractError> {
+    let msg: Ics20Packet = from_json(&packet.data)?;
+    undo_increase_channel_balance(deps.storage, &packet.src.channel_id, msg.amount)?;
+
+    let refund = WasmMsg::Execute {
+        contract_addr: CONFIG.load(deps.storage)?.wtoken.into(),
+        msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: msg.sender.clone(),
+            amount: msg.amount,
+        })?,
+        funds: vec![],
+    };
+
+    REFUND_REPLY_ARGS.save(
+        deps.storage,
+        &RefundReplyArgs {
+            sender: msg.sender.clone(),
+            amount: msg.amount,
+        },
+    )?;
+
+    Ok(IbcBasicResponse::new()
+        .add_submessage(SubMsg::reply_on_error(refund, REFUND_REPLY_ID))
+        .add_attribute("action", action)
+        .add_attribute("sender", msg.sender)
+        .add_attribute("receiver", msg.receiver)
+        .add_attribute("amount", msg.amount)
+        .add_attribute("success", "false")
+        .add_attribute("error", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        testing::{
+            mock_dependencies, mock_env, mock_ibc_channel_close_init, mock_ibc_channel_connect_ack,
+            mock_ibc_channel_open_init, mock_ibc_channel_open_try, mock_ibc_packet_ack,
+            mock_ibc_packet_recv, mock_ibc_packet_timeout, mock_info,
+        },
+        Deps, IbcAcknowledgement, Reply, SubMsgResult,
+    };
+
+    use crate::{
+        contract::{
+            execute, query, reply,
+            tests::{query_channel, send_wtoken, setup, transfer_msg, wtoken, CHANNEL},
+        },
+        msg::{ExecuteMsg, ListChannelsResponse, PendingRefundResponse, QueryMsg},
+    };
+
+    use super::*;
+
+    fn returning_packet(receiver: &str, amount: u128) -> Ics20Packet {
+        Ics20Packet {
+            amount: amount.into(),
+            denom: format!("their-port/channel-1234/{}", wtoken_denom(&wtoken())),
+            receiver: receiver.into(),
+            sender: "osmo1remote".into(),
+            memo: None,
+        }
+    }
+
+    fn sent_packet(sender: &str, amount: u128) -> Ics20Packet {
+        Ics20Packet {
+            amount: amount.into(),
+            denom: wtoken_denom(&wtoken()),
+            receiver: "osmo1remote".into(),
+            sender: sender.into(),
+            memo: None,
+        }
+    }
+
+    fn transfer(recipient: &str, amount: u128) -> WasmMsg {
+        WasmMsg::Execute {
+            contract_addr: wtoken().into(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.into(),
+                amount: amount.into(),
+            })
+            .unwrap(),
+            funds: vec![],
+        }
+    }
+
+    fn outstanding(deps: Deps) -> Uint128 {
+        query_channel(deps, CHANNEL).outstanding
+    }
+
+    #[test]
+    fn channel_handshake() {
+        let mut deps = mock_dependencies();
+        setup(deps.as_mut());
+
+        let msg = mock_ibc_channel_open_init("channel-2", ICS20_ORDERING, "ics20-2");
+        let err = ibc_channel_open(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidIbcVersion {
+                expected: ICS20_VERSION.into(),
+                version: "ics20-2".into()
+            }
+        );
+        let msg = mock_ibc_channel_open_init("channel-2", IbcOrder::Ordered, ICS20_VERSION);
+        let err = ibc_channel_open(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::OnlyUnorderedChannel);
+        let msg = mock_ibc_channel_open_try("channel-2", ICS20_ORDERING, ICS20_VERSION);
+        assert_eq!(
+            ibc_channel_open(deps.as_mut(), mock_env(), msg).unwrap(),
+            None
+        );
+
+        let msg = mock_ibc_channel_connect_ack("channel-2", ICS20_ORDERING, ICS20_VERSION);
+        ibc_channel_connect(deps.as_mut(), mock_env(), msg).unwrap();
+        let channels: ListChannelsResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::ListChannels {}).unwrap())
+                .unwrap();
+        let ids: Vec<_> = channels
+            .channels
+            .iter()
+            .map(|c| c.info.id.as_str())
+            .collect();
+        assert_eq!(ids, vec![CHANNEL, "channel-2"]);
+        assert!(!channels.channels[1].allowed);
+
+        let msg = mock_ibc_channel_close_init(CHANNEL, ICS20_ORDERING, ICS20_VERSION);
+        let err = ibc_channel_close(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::CannotClose);
+    }
+
+    #[test]
+    fn receive_releases_escrow() {
+        let mut deps = mock_dependencies();
+        let sender = deps.api.addr_make("addr0001").to_string();
+        let receiver = deps.api.addr_make("addr0002").to_string();
+        setup(deps.as_mut());
+        send_wtoken(deps.as_mut(), &sender, 100, &transfer_msg(CHANNEL, None)).unwrap();
+
+        let msg = mock_ibc_packet_recv(CHANNEL, &returning_packet(&receiver, 40)).unwrap();
+        let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(res.acknowledgement, Some(ack_success()));
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::reply_on_error(
+                transfer(&receiver, 40),
+                RECEIVE_REPLY_ID
+            )]
+        );
+        assert_eq!(outstanding(deps.as_ref()), Uint128::new(60));
+
+        // only Wtoken sent from this chain can come back
+        let packet = Ics20Packet {
+            denom: "their-port/channel-1234/uatom".into(),
+            ..returning_packet(&receiver, 10)
+        };
+        let msg = mock_ibc_packet_recv(CHANNEL, &packet).unwrap();
+        let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(
+            res.acknowledgement,
+            Some(ack_fail(
+                ContractError::InvalidDenom {
+                    denom: "their-port/channel-1234/uatom".into()
+                }
+                .to_string()
+            ))
+        );
+        assert!(res.messages.is_empty());
+
+        let msg = mock_ibc_packet_recv(CHANNEL, &returning_packet(&receiver, 61)).unwrap();
+        let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(
+            res.acknowledgement,
+            Some(ack_fail(
+                ContractError::InsufficientEscrow {
+                    channel: CHANNEL.into(),
+                    escrowed: Uint128::new(60)
+                }
+                .to_string()
+            ))
+        );
+        assert_eq!(outstanding(deps.as_ref()), Uint128::new(60));
+    }
+
+    #[test]
+    fn failed_payout_restores_escrow() {
+        let mut deps = mock_dependencies();
+        let sender = deps.api.addr_make("addr0001").to_string();
+        let receiver = deps.api.addr_make("addr0002").to_string();
+        setup(deps.as_mut());
+        send_wtoken(deps.as_mut(), &sender, 100, &transfer_msg(CHANNEL, None)).unwrap();
+
+        let msg = mock_ibc_packet_recv(CHANNEL, &returning_packet(&receiver, 40)).unwrap();
+        ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(outstanding(deps.as_ref()), Uint128::new(60));
+
+        let msg = Reply {
+            id: RECEIVE_REPLY_ID,
+            payload: Binary::default(),
+            gas_used: 0,
+            result: SubMsgResult::Err("payout failed".into()),
+        };
+        let res = reply(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(res.data, Some(ack_fail("payout failed".into())));
+        assert_eq!(outstanding(deps.as_ref()), Uint128::new(100));
+    }
+
+    #[test]
+    fn failed_packets_are_refunded() {
+        let mut deps = mock_dependencies();
+        let sender = deps.api.addr_make("addr0001").to_string();
+        setup(deps.as_mut());
+        send_wtoken(deps.as_mut(), &sender, 100, &transfer_msg(CHANNEL, None)).unwrap();
+        send_wtoken(deps.as_mut(), &sender, 30, &transfer_msg(CHANNEL, None)).unwrap();
+
+        let ack = IbcAcknowledgement::new(ack_success());
+        let msg = mock_ibc_packet_ack(CHANNEL, &sent_packet(&sender, 100), ack).unwrap();
+        let res = ibc_packet_ack(deps.as_mut(), mock_env(), msg).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(outstanding(deps.as_ref()), Uint128::new(130));
+
+        let ack = IbcAcknowledgement::new(ack_fail("invalid receiver".into()));
+        let msg = mock_ibc_packet_ack(CHANNEL, &sent_packet(&sender, 20), ack).unwrap();
+        let res = ibc_packet_ack(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::reply_on_error(
+                transfer(&sender, 20),
+                REFUND_REPLY_ID
+            )]
+        );
+        assert_eq!(outstanding(deps.as_ref()), Uint128::new(110));
+
+        let msg = mock_ibc_packet_timeout(CHANNEL, &sent_packet(&sender, 10)).unwrap();
+        let res = ibc_packet_timeout(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::reply_on_error(
+                transfer(&sender, 10),
+                REFUND_REPLY_ID
+            )]
+        );
+        let channel = query_channel(deps.as_ref(), CHANNEL);
+        assert_eq!(channel.outstanding, Uint128::new(100));
+        assert_eq!(channel.total_sent, Uint128::new(100));
+    }
+
+    #[test]
+    fn failed_refunds_can_be_retried() {
+        let mut deps = mock_dependencies();
+        let sender = deps.api.addr_make("addr0001").to_string();
+        setup(deps.as_mut());
+        send_wtoken(deps.as_mut(), &sender, 100, &transfer_msg(CHANNEL, None)).unwrap();
+
+        let pending_refund = |deps: Deps| -> Uint128 {
+            let msg = QueryMsg::PendingRefund {
+                sender: sender.clone(),
+            };
+            let res: PendingRefundResponse =
+                from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.amount
+        };
+
+        // e.g. the sender got frozen, the timeout still goes through
+        let msg = mock_ibc_packet_timeout(CHANNEL, &sent_packet(&sender, 100)).unwrap();
+        ibc_packet_timeout(deps.as_mut(), mock_env(), msg).unwrap();
+        let msg = Reply {
+            id: REFUND_REPLY_ID,
+            payload: Binary::default(),
+            gas_used: 0,
+            result: SubMsgResult::Err("account frozen".into()),
+        };
+        reply(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(outstanding(deps.as_ref()), Uint128::zero());
+        assert_eq!(pending_refund(deps.as_ref()), Uint128::new(100));
+
+        let retry = ExecuteMsg::RetryRefund {
+            sender: sender.clone(),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            retry.clone(),
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(transfer(&sender, 100))]);
+        assert_eq!(pending_refund(deps.as_ref()), Uint128::zero());
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), retry).unwrap_err();
+        assert_eq!(err, ContractError::NoPendingRefund { sender });
+    }
+}
@@ -0,0 +1,73 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::Uint128;
+use cw20::Cw20ReceiveMsg;
+
+use crate::state::{ChannelInfo, Config};
+
+/// The sender becomes admin unless `admin` is set
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub admin: Option<String>,
+    /// The only cw20 accepted by the bridge
+    pub wtoken: String,
+    /// Seconds a packet stays valid when the sender doesn't pick a timeout
+    pub default_timeout: u64,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Wtoken to send over IBC, with a `TransferMsg` payload
+    Receive(Cw20ReceiveMsg),
+    /// Only callable by the admin
+    AllowChannel { channel: String },
+    /// Only callable by the admin. Packets in flight are still settled.
+    DisallowChannel { channel: String },
+    /// Pays out a refund that failed when its packet was acknowledged or timed out.
+    /// Anyone can call it, the Wtoken always goes to `sender`.
+    RetryRefund { sender: String },
+}
+
+/// Payload of Wtoken sent to this contract
+#[cw_serde]
+pub struct TransferMsg {
+    /// Local channel to send the packet over
+    pub channel: String,
+    /// Recipient on the other chain
+    pub remote_address: String,
+    /// Seconds from now, `Config::default_timeout` if unset
+    pub timeout: Option<u64>,
+    pub memo: Option<String>,
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(Config)]
+    Config {},
+    #[returns(ChannelResponse)]
+    Channel { id: String },
+    #[returns(ListChannelsResponse)]
+    ListChannels {},
+    /// Wtoken owed to `sender` after a failed refund
+    #[returns(PendingRefundResponse)]
+    PendingRefund { sender: String },
+}
+
+#[cw_serde]
+pub struct ChannelResponse {
+    pub info: ChannelInfo,
+    pub allowed: bool,
+    /// Wtoken currently escrowed for the channel
+    pub outstanding: Uint128,
+    pub total_sent: Uint128,
+}
+
+#[cw_serde]
+pub struct ListChannelsResponse {
+    pub channels: Vec<ChannelResponse>,
+}
+
+#[cw_serde]
+pub struct PendingRefundResponse {
+    pub amount: Uint128,
+}
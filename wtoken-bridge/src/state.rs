@@ -0,0 +1,122 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Empty, IbcEndpoint, StdResult, Storage, Uint128};
+use cw_storage_plus::{Item, Map};
+
+use crate::error::{ContractError, ContractResult};
+
+pub const CONFIG: Item<Config> = Item::new("config");
+/// Channels the admin allows transfers over, on top of being open
+pub const ALLOWED_CHANNELS: Map<&str, Empty> = Map::new("allowed_channels");
+/// Channels that completed the ICS-20 handshake
+pub const CHANNEL_INFO: Map<&str, ChannelInfo> = Map::new("channel_info");
+/// Wtoken escrowed by this contract for each channel
+pub const CHANNEL_STATE: Map<&str, ChannelState> = Map::new("channel_state");
+/// Packet whose payout is in flight, read back in `reply` if the payout fails
+pub const RECEIVE_REPLY_ARGS: Item<ReceiveReplyArgs> = Item::new("receive_reply_args");
+/// Refund in flight, read back in `reply` if the refund fails
+pub const REFUND_REPLY_ARGS: Item<RefundReplyArgs> = Item::new("refund_reply_args");
+/// Wtoken owed to senders whose refund failed, paid out with `RetryRefund`
+pub const PENDING_REFUNDS: Map<&str, Uint128> = Map::new("pending_refunds");
+
+#[cw_serde]
+pub struct Config {
+    pub admin: Addr,
+    pub wtoken: Addr,
+    /// Seconds a packet stays valid when the sender doesn't pick a timeout
+    pub default_timeout: u64,
+}
+
+#[cw_serde]
+pub struct ChannelInfo {
+    /// Channel id on this chain
+    pub id: String,
+    pub counterparty_endpoint: IbcEndpoint,
+    pub connection_id: String,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct ChannelState {
+    /// Wtoken currently on the other side of the channel
+    pub outstanding: Uint128,
+    /// Wtoken ever sent over the channel, refunds excluded
+    pub total_sent: Uint128,
+}
+
+#[cw_serde]
+pub struct ReceiveReplyArgs {
+    pub channel: String,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct RefundReplyArgs {
+    pub sender: String,
+    pub amount: Uint128,
+}
+
+/// Escrows `amount` for a packet sent over `channel`
+pub fn increase_channel_balance(
+    storage: &mut dyn Storage,
+    channel: &str,
+    amount: Uint128,
+) -> StdResult<()> {
+    CHANNEL_STATE.update(storage, channel, |state| -> StdResult<_> {
+        let mut state = state.unwrap_or_default();
+        state.outstanding += amount;
+        state.total_sent += amount;
+        Ok(state)
+    })?;
+    Ok(())
+}
+
+/// Releases `amount` for a packet received over `channel`. Fails before writing
+/// anything if the channel doesn't escrow enough.
+pub fn reduce_channel_balance(
+    storage: &mut dyn Storage,
+    channel: &str,
+    amount: Uint128,
+) -> ContractResult<()> {
+    let mut state = CHANNEL_STATE
+        .may_load(storage, channel)?
+        .unwrap_or_default();
+    state.outstanding =
+        state
+            .outstanding
+            .checked_sub(amount)
+            .map_err(|_| ContractError::InsufficientEscrow {
+                channel: channel.to_string(),
+                escrowed: state.outstanding,
+            })?;
+    CHANNEL_STATE.save(storage, channel, &state)?;
+    Ok(())
+}
+
+/// Reverts `reduce_channel_balance` after the payout of a received packet failed
+pub fn undo_reduce_channel_balance(
+    storage: &mut dyn Storage,
+    channel: &str,
+    amount: Uint128,
+) -> StdResult<()> {
+    CHANNEL_STATE.update(storage, channel, |state| -> StdResult<_> {
+        let mut state = state.unwrap_or_default();
+        state.outstanding += amount;
+        Ok(state)
+    })?;
+    Ok(())
+}
+
+/// Reverts `increase_channel_balance` for a packet that failed or timed out
+pub fn undo_increase_channel_balance(
+    storage: &mut dyn Storage,
+    channel: &str,
+    amount: Uint128,
+) -> StdResult<()> {
+    CHANNEL_STATE.update(storage, channel, |state| -> StdResult<_> {
+        let mut state = state.unwrap_or_default();
+        state.outstanding = state.outstanding.checked_sub(amount)?;
+        state.total_sent = state.total_sent.checked_sub(amount)?;
+        Ok(state)
+    })?;
+    Ok(())
+}
@@ -0,0 +1,10 @@
+use cosmwasm_schema::write_api;
+use wtoken_converter::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+
+fn main() {
+    write_api! {
+        instantiate: InstantiateMsg,
+        execute: ExecuteMsg,
+        query: QueryMsg
+    }
+}
@@ -0,0 +1,512 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
+};
+
+use crate::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{Config, CONFIG, LOCKED},
+    tokenfactory::{factory_denom, is_valid_package, MsgCreateDenom, TokenFactoryMsg},
+};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let InstantiateMsg {
+        wtoken,
+        subdenom,
+        tokenfactory_package,
+    } = msg;
+    if !is_valid_package(&tokenfactory_package) {
+        return Err(ContractError::InvalidTokenFactoryPackage {
+            package: tokenfactory_package,
+        });
+    }
+    let contract = env.contract.address.to_string();
+    let config = Config {
+        wtoken: deps.api.addr_validate(&wtoken)?,
+        denom: factory_denom(&contract, &subdenom),
+        tokenfactory_package,
+    };
+    CONFIG.save(deps.storage, &config)?;
+    LOCKED.save(deps.storage, &Uint128::zero())?;
+
+    let create_denom = MsgCreateDenom {
+        sender: contract,
+        subdenom,
+    };
+    Ok(Response::new()
+        .add_message(create_denom.into_any(&config.tokenfactory_package))
+        .add_attribute("action", "instantiate")
+        .add_attribute("wtoken", config.wtoken)
+        .add_attribute("denom", config.denom))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    use ExecuteMsg::*;
+    match msg {
+        Receive(receive_msg) => exec::receive(deps, env, info, receive_msg),
+        Release { recipient } => exec::release(deps, env, info, recipient),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    use QueryMsg::*;
+    Ok(match msg {
+        Config {} => to_json_binary(&CONFIG.load(deps.storage)?)?,
+        Supply {} => to_json_binary(&query::supply(deps)?)?,
+    })
+}
+
+mod query {
+    use crate::msg::SupplyResponse;
+
+    use super::*;
+
+    pub fn supply(deps: Deps) -> StdResult<SupplyResponse> {
+        let denom = CONFIG.load(deps.storage)?.denom;
+        Ok(SupplyResponse {
+            locked: LOCKED.load(deps.storage)?,
+            native_supply: deps.querier.query_supply(denom)?.amount,
+        })
+    }
+}
+
+mod exec {
+    use cosmwasm_std::{coin, from_json, BankMsg, WasmMsg};
+    use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+    use cw_utils::must_pay;
+
+    use crate::{
+        msg::ReceiveMsg,
+        tokenfactory::{MsgBurn, MsgMint},
+    };
+
+    use super::*;
+
+    /// Locks the received Wtoken and mints the same amount of the native denom
+    pub fn receive(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: Cw20ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if info.sender != config.wtoken {
+            return Err(ContractError::InvalidWtokenAddress {
+                address: info.sender,
+            });
+        }
+        if msg.amount.is_zero() {
+            return Err(ContractError::ZeroAmountReceived);
+        }
+
+        let ReceiveMsg::Convert { recipient } = if msg.msg.is_empty() {
+            ReceiveMsg::Convert { recipient: None }
+        } else {
+            from_json(&msg.msg)?
+        };
+        let recipient = match recipient {
+            Some(recipient) => deps.api.addr_validate(&recipient)?,
+            None => deps.api.addr_validate(&msg.sender)?,
+        };
+
+        LOCKED.update(deps.storage, |locked| -> StdResult<_> {
+            Ok(locked + msg.amount)
+        })?;
+
+        let minted = coin(msg.amount.u128(), config.denom);
+        let mint = MsgMint::new(env.contract.address, minted.clone());
+        Ok(Response::new()
+            .add_message(mint.into_any(&config.tokenfactory_package))
+            .add_message(BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: vec![minted],
+            })
+            .add_attribute("action", "convert")
+            .add_attribute("from", msg.sender)
+            .add_attribute("to", recipient)
+            .add_attribute("amount", msg.amount))
+    }
+
+    /// Burns the native denom sent along and releases the same amount of Wtoken
+    pub fn release(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipient: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let amount = must_pay(&info, &config.denom)?;
+        let recipient = match recipient {
+            Some(recipient) => deps.api.addr_validate(&recipient)?,
+            None => info.sender.clone(),
+        };
+
+        let locked = LOCKED.load(deps.storage)?.checked_sub(amount)?;
+        LOCKED.save(deps.storage, &locked)?;
+
+        let release = WasmMsg::Execute {
+            contract_addr: config.wtoken.into(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        };
+        let burn = MsgBurn::new(env.contract.address, coin(amount.u128(), config.denom));
+        Ok(Response::new()
+            .add_message(burn.into_any(&config.tokenfactory_package))
+            .add_message(release)
+            .add_attribute("action", "release")
+            .add_attribute("from", info.sender)
+            .add_attribute("to", recipient)
+            .add_attribute("amount", amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        coin, coins, from_json,
+        testing::{mock_dependencies, mock_env, mock_info, MockApi},
+        Addr, BankMsg, CosmosMsg, SubMsg, WasmMsg,
+    };
+    use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+    use cw_utils::PaymentError;
+
+    use prost::Message;
+
+    use crate::{
+        msg::{ReceiveMsg, SupplyResponse},
+        tokenfactory::{MsgBurn, MsgMint},
+    };
+
+    use super::*;
+
+    const SUBDENOM: &str = "uwtk";
+    const PACKAGE: &str = "osmosis.tokenfactory.v1beta1";
+
+    fn wtoken() -> Addr {
+        MockApi::default().addr_make("wtoken")
+    }
+
+    fn denom() -> String {
+        factory_denom(mock_env().contract.address.as_str(), SUBDENOM)
+    }
+
+    fn do_instantiate(deps: DepsMut) -> Response {
+        let msg = InstantiateMsg {
+            wtoken: wtoken().into(),
+            subdenom: SUBDENOM.into(),
+            tokenfactory_package: PACKAGE.into(),
+        };
+        instantiate(deps, mock_env(), mock_info("creator", &[]), msg).unwrap()
+    }
+
+    fn release(
+        deps: DepsMut,
+        sender: &str,
+        amount: u128,
+        recipient: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Release { recipient };
+        let info = mock_info(sender, &coins(amount, denom()));
+        execute(deps, mock_env(), info, msg)
+    }
+
+    /// Decodes a token factory message of the response
+    fn decode<T: Message + Default>(msg: &SubMsg, type_url: &str) -> T {
+        let CosmosMsg::Any(any) = &msg.msg else {
+            panic!("expected a token factory message");
+        };
+        assert_eq!(any.type_url, type_url);
+        T::decode(any.value.as_slice()).unwrap()
+    }
+
+    fn wtoken_transfer(recipient: &str, amount: u128) -> SubMsg {
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: wtoken().into(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.into(),
+                amount: amount.into(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    }
+
+    fn convert(
+        deps: DepsMut,
+        sender: &str,
+        amount: u128,
+        msg: Binary,
+    ) -> Result<Response, ContractError> {
+        let receive_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.into(),
+            amount: amount.into(),
+            msg,
+        });
+        execute(
+            deps,
+            mock_env(),
+            mock_info(wtoken().as_str(), &[]),
+            receive_msg,
+        )
+    }
+
+    fn query_supply(deps: Deps) -> SupplyResponse {
+        from_json(query(deps, mock_env(), QueryMsg::Supply {}).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn instantiate_creates_denom() {
+        let mut deps = mock_dependencies();
+        let res = do_instantiate(deps.as_mut());
+
+        let create_denom = MsgCreateDenom {
+            sender: mock_env().contract.address.into(),
+            subdenom: SUBDENOM.into(),
+        };
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(create_denom.clone().into_any(PACKAGE))]
+        );
+        let decoded: MsgCreateDenom = decode(
+            &res.messages[0],
+            "/osmosis.tokenfactory.v1beta1.MsgCreateDenom",
+        );
+        assert_eq!(decoded, create_denom);
+
+        let config: Config =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(config.denom, denom());
+        assert_eq!(config.tokenfactory_package, PACKAGE);
+    }
+
+    #[test]
+    fn tokenfactory_package_is_configurable() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+
+        for package in [
+            "",
+            "/osmosis.tokenfactory.v1beta1",
+            "osmosis..v1",
+            "osmosis tf",
+        ] {
+            let msg = InstantiateMsg {
+                wtoken: wtoken().into(),
+                subdenom: SUBDENOM.into(),
+                tokenfactory_package: package.into(),
+            };
+            let err =
+                instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidTokenFactoryPackage {
+                    package: package.into()
+                }
+            );
+        }
+
+        let msg = InstantiateMsg {
+            wtoken: wtoken().into(),
+            subdenom: SUBDENOM.into(),
+            tokenfactory_package: "example.tokenfactory.v1".into(),
+        };
+        let res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        decode::<MsgCreateDenom>(&res.messages[0], "/example.tokenfactory.v1.MsgCreateDenom");
+
+        let res = convert(deps.as_mut(), &addr1, 100, Binary::default()).unwrap();
+        decode::<MsgMint>(&res.messages[0], "/example.tokenfactory.v1.MsgMint");
+        let res = release(deps.as_mut(), &addr1, 100, None).unwrap();
+        decode::<MsgBurn>(&res.messages[0], "/example.tokenfactory.v1.MsgBurn");
+    }
+
+    #[test]
+    fn convert_and_release() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let addr2 = deps.api.addr_make("addr0002").to_string();
+        let contract = mock_env().contract.address;
+        do_instantiate(deps.as_mut());
+
+        let res = convert(deps.as_mut(), &addr1, 100, Binary::default()).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(MsgMint::new(contract.clone(), coin(100, denom())).into_any(PACKAGE)),
+                SubMsg::new(BankMsg::Send {
+                    to_address: addr1.clone(),
+                    amount: coins(100, denom()),
+                }),
+            ]
+        );
+
+        let msg = to_json_binary(&ReceiveMsg::Convert {
+            recipient: Some(addr2.clone()),
+        })
+        .unwrap();
+        let res = convert(deps.as_mut(), &addr1, 50, msg).unwrap();
+        assert_eq!(
+            res.messages[1],
+            SubMsg::new(BankMsg::Send {
+                to_address: addr2.clone(),
+                amount: coins(50, denom()),
+            })
+        );
+
+        // the chain mints what the contract asked for
+        deps.querier
+            .bank
+            .update_balance(&addr1, coins(100, denom()));
+        deps.querier.bank.update_balance(&addr2, coins(50, denom()));
+        assert_eq!(
+            query_supply(deps.as_ref()),
+            SupplyResponse {
+                locked: Uint128::new(150),
+                native_supply: Uint128::new(150),
+            }
+        );
+
+        let res = release(deps.as_mut(), &addr2, 50, None).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(MsgBurn::new(contract, coin(50, denom())).into_any(PACKAGE)),
+                wtoken_transfer(&addr2, 50),
+            ]
+        );
+        deps.querier.bank.update_balance(&addr2, vec![]);
+        assert_eq!(
+            query_supply(deps.as_ref()),
+            SupplyResponse {
+                locked: Uint128::new(100),
+                native_supply: Uint128::new(100),
+            }
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let contract = mock_env().contract.address.to_string();
+        do_instantiate(deps.as_mut());
+
+        let res = convert(deps.as_mut(), &addr1, 100, Binary::default()).unwrap();
+        let mint: MsgMint = decode(&res.messages[0], "/osmosis.tokenfactory.v1beta1.MsgMint");
+        assert_eq!(mint.sender, contract);
+        assert_eq!(mint.amount, Some(coin(100, denom()).into()));
+        assert_eq!(mint.mint_to_address, "");
+        deps.querier
+            .bank
+            .update_balance(&addr1, coins(100, denom()));
+
+        let res = release(deps.as_mut(), &addr1, 100, None).unwrap();
+        let burn: MsgBurn = decode(&res.messages[0], "/osmosis.tokenfactory.v1beta1.MsgBurn");
+        assert_eq!(burn.sender, contract);
+        assert_eq!(burn.amount, Some(coin(100, denom()).into()));
+        assert_eq!(burn.burn_from_address, "");
+        assert_eq!(res.messages[1], wtoken_transfer(&addr1, 100));
+
+        // everything converted came back
+        deps.querier.bank.update_balance(&addr1, vec![]);
+        assert_eq!(
+            query_supply(deps.as_ref()),
+            SupplyResponse {
+                locked: Uint128::zero(),
+                native_supply: Uint128::zero(),
+            }
+        );
+    }
+
+    #[test]
+    fn release_burns_what_was_sent() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let addr2 = deps.api.addr_make("addr0002").to_string();
+        let contract = mock_env().contract.address;
+        do_instantiate(deps.as_mut());
+        convert(deps.as_mut(), &addr1, 100, Binary::default()).unwrap();
+
+        // the Wtoken can go to someone else than the holder of the native denom
+        let res = release(deps.as_mut(), &addr1, 30, Some(addr2.clone())).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(MsgBurn::new(contract, coin(30, denom())).into_any(PACKAGE)),
+                wtoken_transfer(&addr2, 30),
+            ]
+        );
+        assert_eq!(query_supply(deps.as_ref()).locked, Uint128::new(70));
+
+        // more than was ever converted can't be released
+        let err = release(deps.as_mut(), &addr1, 71, None).unwrap_err();
+        assert!(matches!(err, ContractError::OverflowError(_)));
+        assert_eq!(query_supply(deps.as_ref()).locked, Uint128::new(70));
+
+        let msg = ExecuteMsg::Release {
+            recipient: Some("invalid".into()),
+        };
+        let info = mock_info(&addr1, &coins(10, denom()));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(query_supply(deps.as_ref()).locked, Uint128::new(70));
+    }
+
+    #[test]
+    fn rejects_other_tokens() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let other_token = deps.api.addr_make("other_token");
+        do_instantiate(deps.as_mut());
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: addr1.clone(),
+            amount: 100u128.into(),
+            msg: Binary::default(),
+        });
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(other_token.as_str(), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidWtokenAddress {
+                address: other_token
+            }
+        );
+
+        let err = convert(deps.as_mut(), &addr1, 0, Binary::default()).unwrap_err();
+        assert_eq!(err, ContractError::ZeroAmountReceived);
+
+        let msg = ExecuteMsg::Release { recipient: None };
+        let info = mock_info(&addr1, &coins(10, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::PaymentError(PaymentError::MissingDenom(denom()))
+        );
+        let info = mock_info(&addr1, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::PaymentError(PaymentError::NoFunds {}));
+        assert_eq!(query_supply(deps.as_ref()).locked, Uint128::zero());
+    }
+}
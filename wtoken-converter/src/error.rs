@@ -0,0 +1,21 @@
+use cosmwasm_std::{Addr, OverflowError, StdError};
+use cw_utils::PaymentError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    StdError(#[from] StdError),
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+    #[error("{0}")]
+    PaymentError(#[from] PaymentError),
+    #[error("{address} is not wtoken address")]
+    InvalidWtokenAddress { address: Addr },
+    #[error("zero amount received")]
+    ZeroAmountReceived,
+    #[error("{package} is not a valid proto package")]
+    InvalidTokenFactoryPackage { package: String },
+}
+
+pub type ContractResult<T> = Result<T, ContractError>;
@@ -0,0 +1,49 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::Uint128;
+use cw20::Cw20ReceiveMsg;
+
+use crate::state::Config;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub wtoken: String,
+    /// Subdenom of the native token, e.g. `uwtk`
+    pub subdenom: String,
+    /// Proto package the chain registers its token factory messages under, e.g.
+    /// `osmosis.tokenfactory.v1beta1`. Check it against the chain before instantiating,
+    /// every conversion fails if the chain doesn't know the type URLs.
+    pub tokenfactory_package: String,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Wtoken to lock, minting the same amount of the native denom
+    Receive(Cw20ReceiveMsg),
+    /// Burns the native denom sent along and releases the same amount of Wtoken
+    /// to `recipient`, or the sender
+    Release { recipient: Option<String> },
+}
+
+/// Payload of Wtoken sent to this contract. An empty payload converts for the sender.
+#[cw_serde]
+pub enum ReceiveMsg {
+    Convert { recipient: Option<String> },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(Config)]
+    Config {},
+    #[returns(SupplyResponse)]
+    Supply {},
+}
+
+/// `locked` and `native_supply` are always equal
+#[cw_serde]
+pub struct SupplyResponse {
+    /// Wtoken locked by the converter
+    pub locked: Uint128,
+    /// Bank supply of the native denom
+    pub native_supply: Uint128,
+}
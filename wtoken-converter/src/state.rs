@@ -0,0 +1,16 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::Item;
+
+pub const CONFIG: Item<Config> = Item::new("config");
+/// Wtoken held against the native tokens in circulation
+pub const LOCKED: Item<Uint128> = Item::new("locked");
+
+#[cw_serde]
+pub struct Config {
+    pub wtoken: Addr,
+    /// Token factory denom created on instantiation, `factory/<contract>/<subdenom>`
+    pub denom: String,
+    /// Proto package of the chain's token factory messages
+    pub tokenfactory_package: String,
+}
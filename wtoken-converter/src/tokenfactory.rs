@@ -0,0 +1,111 @@
+//! Token factory messages, with the fields of `osmosis.tokenfactory.v1beta1`.
+//! Chains forking the module register them under their own proto package, so the
+//! package is part of the contract config and not hard-coded here.
+
+use cosmwasm_std::{AnyMsg, Binary, Coin};
+use prost::Message;
+
+pub fn factory_denom(creator: &str, subdenom: &str) -> String {
+    format!("factory/{creator}/{subdenom}")
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoCoin {
+    #[prost(string, tag = "1")]
+    pub denom: String,
+    #[prost(string, tag = "2")]
+    pub amount: String,
+}
+
+impl From<Coin> for ProtoCoin {
+    fn from(coin: Coin) -> Self {
+        Self {
+            denom: coin.denom,
+            amount: coin.amount.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgCreateDenom {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(string, tag = "2")]
+    pub subdenom: String,
+}
+
+/// Mints to the sender
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgMint {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(message, optional, tag = "2")]
+    pub amount: Option<ProtoCoin>,
+    /// Empty for the sender
+    #[prost(string, tag = "3")]
+    pub mint_to_address: String,
+}
+
+/// Burns from the sender
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgBurn {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(message, optional, tag = "2")]
+    pub amount: Option<ProtoCoin>,
+    /// Empty for the sender
+    #[prost(string, tag = "3")]
+    pub burn_from_address: String,
+}
+
+/// A token factory message, encoded as `/<package>.<NAME>`
+pub trait TokenFactoryMsg: Message + Sized {
+    const NAME: &'static str;
+
+    fn into_any(self, package: &str) -> AnyMsg {
+        AnyMsg {
+            type_url: format!("/{package}.{}", Self::NAME),
+            value: Binary::from(self.encode_to_vec()),
+        }
+    }
+}
+
+/// Proto package names are dot separated identifiers, e.g. `osmosis.tokenfactory.v1beta1`
+pub fn is_valid_package(package: &str) -> bool {
+    !package.is_empty()
+        && package.split('.').all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+impl TokenFactoryMsg for MsgCreateDenom {
+    const NAME: &'static str = "MsgCreateDenom";
+}
+
+impl TokenFactoryMsg for MsgMint {
+    const NAME: &'static str = "MsgMint";
+}
+
+impl TokenFactoryMsg for MsgBurn {
+    const NAME: &'static str = "MsgBurn";
+}
+
+impl MsgMint {
+    pub fn new(sender: impl Into<String>, amount: Coin) -> Self {
+        Self {
+            sender: sender.into(),
+            amount: Some(amount.into()),
+            mint_to_address: String::new(),
+        }
+    }
+}
+
+impl MsgBurn {
+    pub fn new(sender: impl Into<String>, amount: Coin) -> Self {
+        Self {
+            sender: sender.into(),
+            amount: Some(amount.into()),
+            burn_from_address: String::new(),
+        }
+    }
+}
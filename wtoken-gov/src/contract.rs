@@ -0,0 +1,795 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_json_binary, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage,
+    Uint128,
+};
+
+use crate::{
+    error::ContractError,
+    msg::{
+        ExecuteMsg, InstantiateMsg, QueryMsg, TotalPowerAtHeightResponse, VotingModuleQueryMsg,
+        VotingPowerAtHeightResponse,
+    },
+    state::{Config, Proposal, CONFIG, PROPOSALS, PROPOSAL_COUNT},
+};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let InstantiateMsg {
+        voting_module,
+        quorum,
+        threshold,
+        voting_period,
+        execution_delay,
+        execution_period,
+    } = msg;
+    let config = Config {
+        voting_module: deps.api.addr_validate(&voting_module)?,
+        quorum,
+        threshold,
+        voting_period,
+        execution_delay,
+        execution_period,
+    };
+    validate_config(&config)?;
+    CONFIG.save(deps.storage, &config)?;
+    PROPOSAL_COUNT.save(deps.storage, &0)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "instantiate")
+        .add_attribute("voting_module", config.voting_module))
+}
+
+fn validate_config(config: &Config) -> Result<(), ContractError> {
+    let valid = |share: Decimal| !share.is_zero() && share <= Decimal::one();
+    if !valid(config.quorum) || !valid(config.threshold) {
+        return Err(ContractError::InvalidThreshold);
+    }
+    if config.voting_period == 0 {
+        return Err(ContractError::InvalidVotingPeriod);
+    }
+    if config.execution_period == 0 {
+        return Err(ContractError::InvalidExecutionPeriod);
+    }
+    Ok(())
+}
+
+fn load_proposal(storage: &dyn Storage, id: u64) -> Result<Proposal, ContractError> {
+    PROPOSALS
+        .may_load(storage, id)?
+        .ok_or(ContractError::ProposalNotFound { id })
+}
+
+fn voting_power(deps: Deps, address: &str, height: u64) -> StdResult<Uint128> {
+    let voting_module = CONFIG.load(deps.storage)?.voting_module;
+    let res: VotingPowerAtHeightResponse = deps.querier.query_wasm_smart(
+        voting_module,
+        &VotingModuleQueryMsg::VotingPowerAtHeight {
+            address: address.into(),
+            height: Some(height),
+        },
+    )?;
+    Ok(res.power)
+}
+
+fn total_power(deps: Deps, height: u64) -> StdResult<Uint128> {
+    let voting_module = CONFIG.load(deps.storage)?.voting_module;
+    let res: TotalPowerAtHeightResponse = deps.querier.query_wasm_smart(
+        voting_module,
+        &VotingModuleQueryMsg::TotalPowerAtHeight {
+            height: Some(height),
+        },
+    )?;
+    Ok(res.power)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    use ExecuteMsg::*;
+    match msg {
+        Propose {
+            title,
+            description,
+            msgs,
+        } => exec::propose(deps, env, info, title, description, msgs),
+        Vote { proposal_id, vote } => exec::vote(deps, env, info, proposal_id, vote),
+        Execute { proposal_id } => exec::execute_proposal(deps, env, proposal_id),
+        UpdateConfig {
+            quorum,
+            threshold,
+            voting_period,
+            execution_delay,
+            execution_period,
+        } => {
+            let voting_module = CONFIG.load(deps.storage)?.voting_module;
+            let config = Config {
+                voting_module,
+                quorum,
+                threshold,
+                voting_period,
+                execution_delay,
+                execution_period,
+            };
+            exec::update_config(deps, env, info, config)
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    use QueryMsg::*;
+    Ok(match msg {
+        Config {} => to_json_binary(&CONFIG.load(deps.storage)?)?,
+        Proposal { proposal_id } => to_json_binary(&query::proposal(deps, env, proposal_id)?)?,
+        ListProposals { start_after, limit } => {
+            to_json_binary(&query::list_proposals(deps, env, start_after, limit)?)?
+        }
+        Ballot { proposal_id, voter } => to_json_binary(&query::ballot(deps, proposal_id, voter)?)?,
+    })
+}
+
+mod query {
+    use cosmwasm_std::Order;
+    use cw_storage_plus::Bound;
+
+    use crate::{
+        msg::{ListProposalsResponse, ProposalResponse},
+        state::{Ballot, BALLOTS},
+    };
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
+
+    use super::*;
+
+    pub fn proposal(deps: Deps, env: Env, id: u64) -> StdResult<ProposalResponse> {
+        let proposal = PROPOSALS.load(deps.storage, id)?;
+        Ok(ProposalResponse {
+            id,
+            status: proposal.status(env.block.time),
+            proposal,
+        })
+    }
+
+    pub fn list_proposals(
+        deps: Deps,
+        env: Env,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<ListProposalsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let proposals = PROPOSALS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| {
+                let (id, proposal) = item?;
+                Ok(ProposalResponse {
+                    id,
+                    status: proposal.status(env.block.time),
+                    proposal,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(ListProposalsResponse { proposals })
+    }
+
+    pub fn ballot(deps: Deps, proposal_id: u64, voter: String) -> StdResult<Option<Ballot>> {
+        let voter = deps.api.addr_validate(&voter)?;
+        BALLOTS.may_load(deps.storage, (proposal_id, &voter))
+    }
+}
+
+mod exec {
+    use cosmwasm_std::CosmosMsg;
+
+    use crate::state::{Ballot, Status, Vote, Votes, BALLOTS};
+
+    use super::*;
+
+    /// Voting power is taken at the start of the proposal block, so stake added
+    /// after a proposal is created doesn't count for it
+    pub fn propose(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        title: String,
+        description: String,
+        msgs: Vec<CosmosMsg>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let height = env.block.height;
+        let total_power = total_power(deps.as_ref(), height)?;
+        if total_power.is_zero()
+            || voting_power(deps.as_ref(), info.sender.as_str(), height)?.is_zero()
+        {
+            return Err(ContractError::NoVotingPower);
+        }
+
+        let voting_end = env.block.time.plus_seconds(config.voting_period);
+        let executable_at = voting_end.plus_seconds(config.execution_delay);
+        let proposal = Proposal {
+            title,
+            description,
+            proposer: info.sender,
+            msgs,
+            start_height: height,
+            voting_end,
+            executable_at,
+            expires_at: executable_at.plus_seconds(config.execution_period),
+            total_power,
+            votes: Votes::default(),
+            executed: false,
+            quorum: config.quorum,
+            threshold: config.threshold,
+        };
+        let id = PROPOSAL_COUNT.load(deps.storage)? + 1;
+        PROPOSAL_COUNT.save(deps.storage, &id)?;
+        PROPOSALS.save(deps.storage, id, &proposal)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "propose")
+            .add_attribute("proposal_id", id.to_string())
+            .add_attribute("proposer", proposal.proposer))
+    }
+
+    pub fn vote(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        proposal_id: u64,
+        vote: Vote,
+    ) -> Result<Response, ContractError> {
+        let mut proposal = load_proposal(deps.storage, proposal_id)?;
+        if proposal.status(env.block.time) != Status::Open {
+            return Err(ContractError::VotingClosed);
+        }
+        if BALLOTS.has(deps.storage, (proposal_id, &info.sender)) {
+            return Err(ContractError::AlreadyVoted);
+        }
+        let power = voting_power(deps.as_ref(), info.sender.as_str(), proposal.start_height)?;
+        if power.is_zero() {
+            return Err(ContractError::NoVotingPower);
+        }
+
+        proposal.votes.add(&vote, power);
+        PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+        BALLOTS.save(
+            deps.storage,
+            (proposal_id, &info.sender),
+            &Ballot {
+                vote: vote.clone(),
+                power,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "vote")
+            .add_attribute("proposal_id", proposal_id.to_string())
+            .add_attribute("voter", info.sender)
+            .add_attribute("vote", format!("{vote:?}").to_lowercase())
+            .add_attribute("power", power))
+    }
+
+    pub fn execute_proposal(
+        deps: DepsMut,
+        env: Env,
+        proposal_id: u64,
+    ) -> Result<Response, ContractError> {
+        let mut proposal = load_proposal(deps.storage, proposal_id)?;
+        match proposal.status(env.block.time) {
+            Status::Executed => return Err(ContractError::AlreadyExecuted),
+            Status::Open | Status::Rejected => return Err(ContractError::NotPassed),
+            Status::Expired => {
+                return Err(ContractError::ProposalExpired {
+                    expires_at: proposal.expires_at,
+                })
+            }
+            Status::Passed => {}
+        }
+        if env.block.time < proposal.executable_at {
+            return Err(ContractError::ExecutionDelayNotOver {
+                executable_at: proposal.executable_at,
+            });
+        }
+
+        proposal.executed = true;
+        PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+
+        Ok(Response::new()
+            .add_messages(proposal.msgs)
+            .add_attribute("action", "execute")
+            .add_attribute("proposal_id", proposal_id.to_string()))
+    }
+
+    pub fn update_config(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        config: Config,
+    ) -> Result<Response, ContractError> {
+        if info.sender != env.contract.address {
+            return Err(ContractError::Unauthorized);
+        }
+        validate_config(&config)?;
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new().add_attribute("action", "update_config"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use cosmwasm_std::{
+        coins, from_json,
+        testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage},
+        Addr, BankMsg, ContractResult, CosmosMsg, OwnedDeps, SubMsg, SystemResult, WasmMsg,
+        WasmQuery,
+    };
+
+    use crate::{
+        msg::{ListProposalsResponse, ProposalResponse},
+        state::{Ballot, Status, Vote},
+    };
+
+    use super::*;
+
+    const VOTING_PERIOD: u64 = 100;
+    const EXECUTION_DELAY: u64 = 50;
+    const EXECUTION_PERIOD: u64 = 200;
+
+    fn voting_module() -> Addr {
+        MockApi::default().addr_make("reward-contract")
+    }
+
+    fn voter(name: &str) -> Addr {
+        MockApi::default().addr_make(name)
+    }
+
+    /// Voting module with `voter1` to `voter3` holding 60, 30 and 10 of 100
+    fn setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        let powers: HashMap<String, u128> = [("voter1", 60), ("voter2", 30), ("voter3", 10)]
+            .into_iter()
+            .map(|(name, power)| (voter(name).to_string(), power))
+            .collect();
+        deps.querier.update_wasm(move |query| {
+            let WasmQuery::Smart { msg, .. } = query else {
+                panic!("unexpected query");
+            };
+            let res = match from_json(msg).unwrap() {
+                VotingModuleQueryMsg::VotingPowerAtHeight { address, height } => {
+                    to_json_binary(&VotingPowerAtHeightResponse {
+                        power: powers.get(&address).copied().unwrap_or_default().into(),
+                        height: height.unwrap(),
+                    })
+                }
+                VotingModuleQueryMsg::TotalPowerAtHeight { height } => {
+                    to_json_binary(&TotalPowerAtHeightResponse {
+                        power: 100u128.into(),
+                        height: height.unwrap(),
+                    })
+                }
+            };
+            SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+        });
+
+        let msg = InstantiateMsg {
+            voting_module: voting_module().into(),
+            quorum: Decimal::percent(30),
+            threshold: Decimal::percent(50),
+            voting_period: VOTING_PERIOD,
+            execution_delay: EXECUTION_DELAY,
+            execution_period: EXECUTION_PERIOD,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        deps
+    }
+
+    fn propose(deps: DepsMut, proposer: &Addr, msgs: Vec<CosmosMsg>) -> Result<u64, ContractError> {
+        let msg = ExecuteMsg::Propose {
+            title: "Lower the protocol fee".into(),
+            description: "".into(),
+            msgs,
+        };
+        let res = execute(deps, mock_env(), mock_info(proposer.as_str(), &[]), msg)?;
+        let id = res
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "proposal_id")
+            .unwrap();
+        Ok(id.value.parse().unwrap())
+    }
+
+    fn vote(
+        deps: DepsMut,
+        env: Env,
+        voter: &Addr,
+        proposal_id: u64,
+        vote: Vote,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Vote { proposal_id, vote };
+        execute(deps, env, mock_info(voter.as_str(), &[]), msg)
+    }
+
+    fn execute_proposal(
+        deps: DepsMut,
+        env: Env,
+        proposal_id: u64,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Execute { proposal_id };
+        execute(deps, env, mock_info("anyone", &[]), msg)
+    }
+
+    fn status(deps: Deps, env: Env, proposal_id: u64) -> Status {
+        let msg = QueryMsg::Proposal { proposal_id };
+        let res: ProposalResponse = from_json(query(deps, env, msg).unwrap()).unwrap();
+        res.status
+    }
+
+    #[test]
+    fn proposal_lifecycle() {
+        let mut deps = setup();
+        let (voter1, voter2) = (voter("voter1"), voter("voter2"));
+        let update_fees = CosmosMsg::from(WasmMsg::Execute {
+            contract_addr: voting_module().into(),
+            msg: Binary::from(br#"{"update_fees":{}}"#),
+            funds: vec![],
+        });
+        let payout = CosmosMsg::from(BankMsg::Send {
+            to_address: voter2.to_string(),
+            amount: coins(10, "usdsim"),
+        });
+
+        let err = propose(deps.as_mut(), &voter("outsider"), vec![]).unwrap_err();
+        assert_eq!(err, ContractError::NoVotingPower);
+        let id = propose(
+            deps.as_mut(),
+            &voter1,
+            vec![update_fees.clone(), payout.clone()],
+        )
+        .unwrap();
+        assert_eq!(id, 1);
+
+        let mut env = mock_env();
+        vote(deps.as_mut(), env.clone(), &voter1, id, Vote::Yes).unwrap();
+        let err = vote(deps.as_mut(), env.clone(), &voter1, id, Vote::No).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyVoted);
+        let err = vote(deps.as_mut(), env.clone(), &voter("outsider"), id, Vote::No).unwrap_err();
+        assert_eq!(err, ContractError::NoVotingPower);
+        vote(deps.as_mut(), env.clone(), &voter2, id, Vote::No).unwrap();
+
+        let err = execute_proposal(deps.as_mut(), env.clone(), id).unwrap_err();
+        assert_eq!(err, ContractError::NotPassed);
+        assert_eq!(status(deps.as_ref(), env.clone(), id), Status::Open);
+
+        env.block.time = env.block.time.plus_seconds(VOTING_PERIOD);
+        assert_eq!(status(deps.as_ref(), env.clone(), id), Status::Passed);
+        let err = vote(deps.as_mut(), env.clone(), &voter("voter3"), id, Vote::No).unwrap_err();
+        assert_eq!(err, ContractError::VotingClosed);
+        let err = execute_proposal(deps.as_mut(), env.clone(), id).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ExecutionDelayNotOver {
+                executable_at: env.block.time.plus_seconds(EXECUTION_DELAY)
+            }
+        );
+
+        env.block.time = env.block.time.plus_seconds(EXECUTION_DELAY);
+        let res = execute_proposal(deps.as_mut(), env.clone(), id).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(update_fees), SubMsg::new(payout)]
+        );
+        let err = execute_proposal(deps.as_mut(), env.clone(), id).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyExecuted);
+        assert_eq!(status(deps.as_ref(), env, id), Status::Executed);
+
+        let msg = QueryMsg::Ballot {
+            proposal_id: id,
+            voter: voter2.to_string(),
+        };
+        let ballot: Option<Ballot> =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(
+            ballot,
+            Some(Ballot {
+                vote: Vote::No,
+                power: Uint128::new(30)
+            })
+        );
+    }
+
+    #[test]
+    fn proposals_need_quorum_and_threshold() {
+        let mut deps = setup();
+        let (voter1, voter2, voter3) = (voter("voter1"), voter("voter2"), voter("voter3"));
+        let mut env = mock_env();
+
+        // 10% voted, under the 30% quorum
+        let low_turnout = propose(deps.as_mut(), &voter3, vec![]).unwrap();
+        vote(deps.as_mut(), env.clone(), &voter3, low_turnout, Vote::Yes).unwrap();
+
+        // 30 yes against 60 no
+        let outvoted = propose(deps.as_mut(), &voter2, vec![]).unwrap();
+        vote(deps.as_mut(), env.clone(), &voter1, outvoted, Vote::No).unwrap();
+        vote(deps.as_mut(), env.clone(), &voter2, outvoted, Vote::Yes).unwrap();
+
+        // abstentions count for the quorum only
+        let abstained = propose(deps.as_mut(), &voter1, vec![]).unwrap();
+        vote(
+            deps.as_mut(),
+            env.clone(),
+            &voter1,
+            abstained,
+            Vote::Abstain,
+        )
+        .unwrap();
+        vote(deps.as_mut(), env.clone(), &voter3, abstained, Vote::Yes).unwrap();
+
+        env.block.time = env.block.time.plus_seconds(VOTING_PERIOD + EXECUTION_DELAY);
+        let msg = QueryMsg::ListProposals {
+            start_after: None,
+            limit: None,
+        };
+        let res: ListProposalsResponse =
+            from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let statuses: Vec<_> = res
+            .proposals
+            .into_iter()
+            .map(|p| (p.id, p.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (low_turnout, Status::Rejected),
+                (outvoted, Status::Rejected),
+                (abstained, Status::Passed)
+            ]
+        );
+        let err = execute_proposal(deps.as_mut(), env, outvoted).unwrap_err();
+        assert_eq!(err, ContractError::NotPassed);
+    }
+
+    #[test]
+    fn config_changes_through_proposals() {
+        let mut deps = setup();
+        let env = mock_env();
+        let msg = ExecuteMsg::UpdateConfig {
+            quorum: Decimal::percent(40),
+            threshold: Decimal::percent(60),
+            voting_period: 200,
+            execution_delay: 0,
+            execution_period: 100,
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(voter("voter1").as_str(), &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+
+        let self_info = mock_info(env.contract.address.as_str(), &[]);
+        let invalid = ExecuteMsg::UpdateConfig {
+            quorum: Decimal::zero(),
+            threshold: Decimal::percent(60),
+            voting_period: 200,
+            execution_delay: 0,
+            execution_period: 100,
+        };
+        let err = execute(deps.as_mut(), env.clone(), self_info.clone(), invalid).unwrap_err();
+        assert_eq!(err, ContractError::InvalidThreshold);
+        execute(deps.as_mut(), env.clone(), self_info, msg).unwrap();
+
+        let config: Config =
+            from_json(query(deps.as_ref(), env, QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(config.threshold, Decimal::percent(60));
+        assert_eq!(config.voting_period, 200);
+    }
+
+    #[test]
+    fn passed_proposals_expire() {
+        let mut deps = setup();
+        let voter1 = voter("voter1");
+        let mut env = mock_env();
+        let id = propose(deps.as_mut(), &voter1, vec![]).unwrap();
+        vote(deps.as_mut(), env.clone(), &voter1, id, Vote::Yes).unwrap();
+
+        let expires_at = env
+            .block
+            .time
+            .plus_seconds(VOTING_PERIOD + EXECUTION_DELAY + EXECUTION_PERIOD);
+        env.block.time = expires_at.minus_seconds(1);
+        assert_eq!(status(deps.as_ref(), env.clone(), id), Status::Passed);
+
+        env.block.time = expires_at;
+        assert_eq!(status(deps.as_ref(), env.clone(), id), Status::Expired);
+        let err = execute_proposal(deps.as_mut(), env, id).unwrap_err();
+        assert_eq!(err, ContractError::ProposalExpired { expires_at });
+
+        let self_info = mock_info(mock_env().contract.address.as_str(), &[]);
+        let msg = ExecuteMsg::UpdateConfig {
+            quorum: Decimal::percent(30),
+            threshold: Decimal::percent(50),
+            voting_period: VOTING_PERIOD,
+            execution_delay: EXECUTION_DELAY,
+            execution_period: 0,
+        };
+        let err = execute(deps.as_mut(), mock_env(), self_info, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidExecutionPeriod);
+    }
+
+    /// Runs a proposal against the reward contract itself rather than a mocked voting module
+    #[test]
+    fn proposals_against_the_reward_contract() {
+        use std::{cell::RefCell, rc::Rc};
+
+        use cosmwasm_std::{attr, MsgResponse, Reply, SubMsgResponse, SubMsgResult, SystemError};
+        use cw20::Cw20ReceiveMsg;
+        use reward_contract::msg::{
+            ExecuteMsg as RewardExecuteMsg, InstantiateMsg as RewardInstantiateMsg,
+            QueryMsg as RewardQueryMsg,
+        };
+
+        let wtoken = voter("wtoken");
+        let (voter1, voter2) = (voter("voter1"), voter("voter2"));
+        let mut gov_env = mock_env();
+        gov_env.contract.address = voter("wtoken-gov");
+
+        // stake one block before the proposal, power is taken at the start of its block
+        let mut stake_env = mock_env();
+        stake_env.block.height -= 1;
+        let reward_deps = Rc::new(RefCell::new(mock_dependencies()));
+        {
+            let mut reward_deps = reward_deps.borrow_mut();
+            let msg = RewardInstantiateMsg {
+                wtoken_contract: wtoken.to_string(),
+                receipt_token_code_id: 1,
+                max_stake_per_user: None,
+                max_total_stake: None,
+                staking_access: None,
+                reward_vesting_period: 0,
+                protocol_fee: Decimal::zero(),
+                referral_share: Decimal::zero(),
+                fee_collector: None,
+            };
+            let info = mock_info("creator", &[]);
+            reward_contract::instantiate(reward_deps.as_mut(), stake_env.clone(), info, msg)
+                .unwrap();
+
+            // Protobuf encoded `MsgInstantiateContractResponse` of the receipt token
+            let receipt_token = voter("receipt-token").to_string();
+            let mut data = vec![0x0a, receipt_token.len() as u8];
+            data.extend_from_slice(receipt_token.as_bytes());
+            #[allow(deprecated)]
+            let result = SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+                msg_responses: vec![MsgResponse {
+                    type_url: "/cosmwasm.wasm.v1.MsgInstantiateContractResponse".into(),
+                    value: data.into(),
+                }],
+            });
+            let reply = Reply {
+                id: 1,
+                payload: Binary::default(),
+                gas_used: 0,
+                result,
+            };
+            reward_contract::reply(reward_deps.as_mut(), stake_env.clone(), reply).unwrap();
+
+            for (staker, amount) in [(&voter1, 60u128), (&voter2, 40)] {
+                let msg = RewardExecuteMsg::Receive(Cw20ReceiveMsg {
+                    sender: staker.to_string(),
+                    amount: amount.into(),
+                    msg: Binary::default(),
+                });
+                let info = mock_info(wtoken.as_str(), &[]);
+                reward_contract::execute(reward_deps.as_mut(), stake_env.clone(), info, msg)
+                    .unwrap();
+            }
+
+            let msg = RewardExecuteMsg::UpdateAdmin {
+                admin: gov_env.contract.address.to_string(),
+            };
+            let info = mock_info("creator", &[]);
+            reward_contract::execute(reward_deps.as_mut(), stake_env.clone(), info, msg).unwrap();
+        }
+
+        let mut deps = mock_dependencies();
+        let querier_deps = reward_deps.clone();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg }
+                if voting_module().as_str() == contract_addr =>
+            {
+                let msg: RewardQueryMsg = from_json(msg).unwrap();
+                let res = reward_contract::query(querier_deps.borrow().as_ref(), mock_env(), msg);
+                SystemResult::Ok(res.into())
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "unexpected query".into(),
+            }),
+        });
+        let msg = InstantiateMsg {
+            voting_module: voting_module().into(),
+            quorum: Decimal::percent(30),
+            threshold: Decimal::percent(50),
+            voting_period: VOTING_PERIOD,
+            execution_delay: EXECUTION_DELAY,
+            execution_period: EXECUTION_PERIOD,
+        };
+        instantiate(
+            deps.as_mut(),
+            gov_env.clone(),
+            mock_info("creator", &[]),
+            msg,
+        )
+        .unwrap();
+
+        let update_fees = RewardExecuteMsg::UpdateFees {
+            protocol_fee: Decimal::percent(5),
+            referral_share: Decimal::percent(1),
+            fee_collector: voter1.to_string(),
+        };
+        let msg = ExecuteMsg::Propose {
+            title: "Charge a protocol fee".into(),
+            description: "".into(),
+            msgs: vec![WasmMsg::Execute {
+                contract_addr: voting_module().into(),
+                msg: to_json_binary(&update_fees).unwrap(),
+                funds: vec![],
+            }
+            .into()],
+        };
+        let info = mock_info(voter2.as_str(), &[]);
+        execute(deps.as_mut(), gov_env.clone(), info, msg).unwrap();
+        let res = vote(deps.as_mut(), gov_env.clone(), &voter1, 1, Vote::Yes).unwrap();
+        assert!(res.attributes.contains(&attr("power", "60")));
+        vote(deps.as_mut(), gov_env.clone(), &voter2, 1, Vote::No).unwrap();
+
+        gov_env.block.time = gov_env
+            .block
+            .time
+            .plus_seconds(VOTING_PERIOD + EXECUTION_DELAY);
+        let res = execute_proposal(deps.as_mut(), gov_env.clone(), 1).unwrap();
+
+        let CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr, msg, ..
+        }) = &res.messages[0].msg
+        else {
+            panic!("unexpected message");
+        };
+        assert_eq!(*contract_addr, voting_module().to_string());
+        let info = mock_info(gov_env.contract.address.as_str(), &[]);
+        let msg = from_json(msg).unwrap();
+        let mut reward_deps = reward_deps.borrow_mut();
+        reward_contract::execute(reward_deps.as_mut(), mock_env(), info, msg).unwrap();
+        let config: reward_contract::state::Config = from_json(
+            reward_contract::query(reward_deps.as_ref(), mock_env(), RewardQueryMsg::Config {})
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(config.protocol_fee, Decimal::percent(5));
+        assert_eq!(config.fee_collector, voter1);
+    }
+}
@@ -0,0 +1,34 @@
+use cosmwasm_std::{StdError, Timestamp};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    StdError(#[from] StdError),
+    #[error("Unauthorized")]
+    Unauthorized,
+    #[error("Quorum and threshold must be greater than 0 and at most 1")]
+    InvalidThreshold,
+    #[error("Voting period must be greater than zero")]
+    InvalidVotingPeriod,
+    #[error("Execution period must be greater than zero")]
+    InvalidExecutionPeriod,
+    #[error("No voting power at the proposal height")]
+    NoVotingPower,
+    #[error("Proposal {id} not found")]
+    ProposalNotFound { id: u64 },
+    #[error("Voting on the proposal is closed")]
+    VotingClosed,
+    #[error("Already voted")]
+    AlreadyVoted,
+    #[error("Proposal has not passed")]
+    NotPassed,
+    #[error("Proposal can be executed from {executable_at}")]
+    ExecutionDelayNotOver { executable_at: Timestamp },
+    #[error("Proposal expired at {expires_at}")]
+    ProposalExpired { expires_at: Timestamp },
+    #[error("Proposal already executed")]
+    AlreadyExecuted,
+}
+
+pub type ContractResult<T> = Result<T, ContractError>;
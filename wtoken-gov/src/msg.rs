@@ -0,0 +1,94 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{CosmosMsg, Decimal, Uint128};
+
+use crate::state::{Ballot, Config, Proposal, Status, Vote};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub voting_module: String,
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub voting_period: u64,
+    pub execution_delay: u64,
+    pub execution_period: u64,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Callable by anyone with voting power
+    Propose {
+        title: String,
+        description: String,
+        msgs: Vec<CosmosMsg>,
+    },
+    Vote {
+        proposal_id: u64,
+        vote: Vote,
+    },
+    /// Callable by anyone once a proposal passed and the execution delay is over,
+    /// until the end of the execution period
+    Execute {
+        proposal_id: u64,
+    },
+    /// Only callable by the contract itself, through a proposal.
+    /// Applies to proposals created afterwards.
+    UpdateConfig {
+        quorum: Decimal,
+        threshold: Decimal,
+        voting_period: u64,
+        execution_delay: u64,
+        execution_period: u64,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(Config)]
+    Config {},
+    #[returns(ProposalResponse)]
+    Proposal { proposal_id: u64 },
+    #[returns(ListProposalsResponse)]
+    ListProposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(Option<Ballot>)]
+    Ballot { proposal_id: u64, voter: String },
+}
+
+#[cw_serde]
+pub struct ProposalResponse {
+    pub id: u64,
+    pub proposal: Proposal,
+    pub status: Status,
+}
+
+#[cw_serde]
+pub struct ListProposalsResponse {
+    pub proposals: Vec<ProposalResponse>,
+}
+
+/// Queries of DAO DAO voting modules used to weigh votes
+#[cw_serde]
+pub enum VotingModuleQueryMsg {
+    VotingPowerAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+    TotalPowerAtHeight {
+        height: Option<u64>,
+    },
+}
+
+#[cw_serde]
+pub struct VotingPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+#[cw_serde]
+pub struct TotalPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
@@ -0,0 +1,118 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, CosmosMsg, Decimal, Timestamp, Uint128};
+use cw_storage_plus::{Item, Map};
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const PROPOSAL_COUNT: Item<u64> = Item::new("proposal_count");
+pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+/// (proposal id, voter)
+pub const BALLOTS: Map<(u64, &Addr), Ballot> = Map::new("ballots");
+
+/// Only changed through proposals
+#[cw_serde]
+pub struct Config {
+    /// Contract answering `VotingPowerAtHeight` and `TotalPowerAtHeight`, the reward contract
+    pub voting_module: Addr,
+    /// Share of the total power that has to vote, abstentions included
+    pub quorum: Decimal,
+    /// Share of the yes and no votes that has to be yes
+    pub threshold: Decimal,
+    /// Seconds a proposal is open for votes
+    pub voting_period: u64,
+    /// Seconds between the end of voting and the execution of a passed proposal
+    pub execution_delay: u64,
+    /// Seconds a passed proposal stays executable once the execution delay is over
+    pub execution_period: u64,
+}
+
+#[cw_serde]
+pub enum Vote {
+    Yes,
+    No,
+    Abstain,
+}
+
+#[cw_serde]
+pub struct Ballot {
+    pub vote: Vote,
+    pub power: Uint128,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct Votes {
+    pub yes: Uint128,
+    pub no: Uint128,
+    pub abstain: Uint128,
+}
+
+impl Votes {
+    pub fn add(&mut self, vote: &Vote, power: Uint128) {
+        match vote {
+            Vote::Yes => self.yes += power,
+            Vote::No => self.no += power,
+            Vote::Abstain => self.abstain += power,
+        }
+    }
+
+    pub fn total(&self) -> Uint128 {
+        self.yes + self.no + self.abstain
+    }
+}
+
+#[cw_serde]
+pub enum Status {
+    /// Accepting votes
+    Open,
+    Rejected,
+    /// Executable once the execution delay is over
+    Passed,
+    Executed,
+    /// Passed, but not executed before the end of the execution period
+    Expired,
+}
+
+#[cw_serde]
+pub struct Proposal {
+    pub title: String,
+    pub description: String,
+    pub proposer: Addr,
+    pub msgs: Vec<CosmosMsg>,
+    /// Voting power is taken at the start of this block
+    pub start_height: u64,
+    pub voting_end: Timestamp,
+    pub executable_at: Timestamp,
+    /// Proposals that aren't executed by then can't be executed anymore
+    pub expires_at: Timestamp,
+    pub total_power: Uint128,
+    pub votes: Votes,
+    pub executed: bool,
+    /// Quorum and threshold when the proposal was created
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+}
+
+impl Proposal {
+    pub fn status(&self, now: Timestamp) -> Status {
+        if self.executed {
+            Status::Executed
+        } else if now < self.voting_end {
+            Status::Open
+        } else if !self.is_passed() {
+            Status::Rejected
+        } else if now >= self.expires_at {
+            Status::Expired
+        } else {
+            Status::Passed
+        }
+    }
+
+    fn is_passed(&self) -> bool {
+        let Votes { yes, no, .. } = self.votes;
+        if self.total_power.is_zero() || (yes + no).is_zero() {
+            return false;
+        }
+        Decimal::from_ratio(self.votes.total(), self.total_power) >= self.quorum
+            && Decimal::from_ratio(yes, yes + no) >= self.threshold
+    }
+}
@@ -1,61 +1,313 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cw20_base::contract::{
-    execute_burn, execute_mint, execute_send, execute_transfer, execute_update_minter,
-    query_balance, query_minter, query_token_info,
+use cw20_base::{
+    allowances::{
+        execute_burn_from, execute_decrease_allowance, execute_increase_allowance,
+        execute_send_from, execute_transfer_from, query_allowance,
+    },
+    contract::{
+        execute_burn, execute_mint, execute_send, execute_transfer, execute_update_marketing,
+        execute_update_minter, execute_upload_logo, query_balance, query_download_logo,
+        query_marketing_info, query_minter, query_token_info,
+    },
+    enumerable::{query_all_accounts, query_owner_allowances, query_spender_allowances},
+    state::{ALLOWANCES, ALLOWANCES_SPENDER, BALANCES, TOKEN_INFO},
 };
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
 
 use crate::{
-    error::ContractError,
-    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    error::{CW20BaseError, ContractError},
+    msg::{
+        BalanceChangedHookMsg, ExecuteMsg, FeeConfigResponse, FrozenAccountsResponse,
+        HolderCountResponse, HookInfo, HooksResponse, InstantiateMsg, IsFrozenResponse,
+        MaxBatchSizeResponse, MigrateMsg, MinterAllowanceResponse, MinterInfo, MintersResponse,
+        PauseStateResponse, PermitMsg, PermitNonceResponse, PermitSignDoc, QueryMsg,
+        TotalSupplyAtResponse, TransferFee, VestingCoin, VestingInfoResponse, PERMIT_DOMAIN,
+    },
+    state::{
+        FeeConfig, HookFailurePolicy, MinterQuota, VestingSchedule, ADMIN, BALANCE_SNAPSHOTS,
+        DEFAULT_MAX_BATCH_SIZE, FEE_CONFIG, FEE_EXEMPT, FREEZABLE, FROZEN, GUARDIAN, HOLDER_COUNT,
+        HOOKS, MAX_BATCH_SIZE, MAX_HOOKS, MAX_TRANSFER_FEE_BPS, MAX_VESTING_SCHEDULES, MINTERS,
+        PAUSED, PERMIT_NONCES, TOTAL_SUPPLY_SNAPSHOTS, VESTING,
+    },
 };
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::Bound;
+use semver::Version;
 
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response};
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, Binary, BlockInfo, CanonicalAddr, Deps, DepsMut, Empty, Env, Event,
+    MessageInfo, Order, Reply, Response, StdResult, SubMsg, Uint128,
+};
+use cw20::{AllowanceResponse, BalanceResponse, Cw20Coin};
+
+const CONTRACT_NAME: &str = "crates.io:wtoken";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const CW20_BASE_CONTRACT_NAME: &str = "crates.io:cw20-base";
 
 const TOKEN_NAME: &str = "Wtoken";
 const TOKEN_SYMBOL: &str = "WTK";
 const TOKEN_DECIMALS: u8 = 6;
 
+const IGNORED_HOOK_FAILURE_REPLY_ID: u64 = 1;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     let InstantiateMsg {
+        name,
+        symbol,
+        decimals,
         initial_balances,
         mint,
+        marketing,
+        admin,
+        max_batch_size,
+        initial_vesting,
+        guardian,
+        transfer_fee,
+        freezable,
     } = msg;
 
+    let admin = match admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender.clone(),
+    };
+    let guardian = match guardian {
+        Some(guardian) => deps.api.addr_validate(&guardian)?,
+        None => admin.clone(),
+    };
+    let fee_config = match transfer_fee {
+        Some(transfer_fee) => validate_transfer_fee(deps.as_ref(), transfer_fee)?,
+        None => FeeConfig {
+            fee_bps: 0,
+            collector: admin.clone(),
+        },
+    };
+    FEE_CONFIG.save(deps.storage, &fee_config)?;
+    ADMIN.save(deps.storage, &admin)?;
+    GUARDIAN.save(deps.storage, &guardian)?;
+    PAUSED.save(deps.storage, &false)?;
+    FREEZABLE.save(deps.storage, &freezable.unwrap_or(true))?;
+    MAX_BATCH_SIZE.save(
+        deps.storage,
+        &max_batch_size.unwrap_or(DEFAULT_MAX_BATCH_SIZE),
+    )?;
+
+    // Vested amounts are regular balances, cw20-base wants a single entry per account
+    let mut initial_balances = initial_balances;
+    let initial_vesting = initial_vesting.unwrap_or_default();
+    for VestingCoin {
+        address, amount, ..
+    } in &initial_vesting
+    {
+        match initial_balances
+            .iter_mut()
+            .find(|coin| &coin.address == address)
+        {
+            Some(coin) => coin.amount += *amount,
+            None => initial_balances.push(Cw20Coin {
+                address: address.clone(),
+                amount: *amount,
+            }),
+        }
+    }
+
     let cw20_init_msg = cw20_base::msg::InstantiateMsg {
-        name: TOKEN_NAME.into(),
-        symbol: TOKEN_SYMBOL.into(),
+        name: name.unwrap_or_else(|| TOKEN_NAME.into()),
+        symbol: symbol.unwrap_or_else(|| TOKEN_SYMBOL.into()),
         initial_balances,
-        decimals: TOKEN_DECIMALS,
+        decimals: decimals.unwrap_or(TOKEN_DECIMALS),
         mint,
-        marketing: None,
+        marketing,
+    };
+
+    let block = env.block.clone();
+    let height = env.block.height;
+    let res = cw20_base::contract::instantiate(deps.branch(), env, info, cw20_init_msg)?;
+
+    for VestingCoin {
+        address,
+        amount,
+        vesting,
+    } in initial_vesting
+    {
+        let address = deps.api.addr_validate(&address)?;
+        let schedule = VestingSchedule {
+            amount,
+            curve: vesting,
+        };
+        add_vesting_schedule(deps.branch(), &block, &address, schedule)?;
+    }
+
+    init_balance_state(deps.branch(), height)?;
+    // cw20-base stored its own name and version
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    Ok(res
+        .add_attribute("action", "instantiate")
+        .add_attribute("name", token_info.name)
+        .add_attribute("symbol", token_info.symbol)
+        .add_attribute("decimals", token_info.decimals.to_string())
+        .add_attribute("new_supply", token_info.total_supply))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    let cannot_migrate = || ContractError::CannotMigrate {
+        contract: stored.contract.clone(),
+        version: stored.version.clone(),
     };
+    match stored.contract.as_str() {
+        CONTRACT_NAME => {
+            let stored_version: Version = stored.version.parse()?;
+            let version: Version = CONTRACT_VERSION.parse()?;
+            if stored_version > version {
+                return Err(cannot_migrate());
+            }
+        }
+        // wtoken used to keep the version cw20-base set
+        CW20_BASE_CONTRACT_NAME => {}
+        _ => return Err(cannot_migrate()),
+    }
 
-    cw20_base::contract::instantiate(deps, env, info, cw20_init_msg)?;
+    // State added since the first version, only set when missing so that
+    // migrating between wtoken versions keeps it
+    if !ADMIN.exists(deps.storage) {
+        let admin = msg.admin.ok_or(ContractError::MigrationAdminRequired)?;
+        let admin = deps.api.addr_validate(&admin)?;
+        ADMIN.save(deps.storage, &admin)?;
+        GUARDIAN.save(deps.storage, &admin)?;
+        FEE_CONFIG.save(
+            deps.storage,
+            &FeeConfig {
+                fee_bps: 0,
+                collector: admin,
+            },
+        )?;
+        PAUSED.save(deps.storage, &false)?;
+        FREEZABLE.save(deps.storage, &msg.freezable.unwrap_or(false))?;
+        MAX_BATCH_SIZE.save(deps.storage, &DEFAULT_MAX_BATCH_SIZE)?;
+    }
+    if !HOLDER_COUNT.exists(deps.storage) {
+        init_balance_state(deps.branch(), env.block.height)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
 
-    Ok(Response::new())
+/// Builds the holder count and the first snapshots from the cw20-base balances
+fn init_balance_state(deps: DepsMut, height: u64) -> StdResult<()> {
+    let balances = BALANCES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (account, balance) in &balances {
+        BALANCE_SNAPSHOTS.save(deps.storage, account, balance, height)?;
+    }
+    let holders = balances
+        .iter()
+        .filter(|(_, balance)| !balance.is_zero())
+        .count() as u64;
+    HOLDER_COUNT.save(deps.storage, &holders)?;
+    let total_supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    TOTAL_SUPPLY_SNAPSHOTS.save(deps.storage, &total_supply, height)?;
+    Ok(())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     use QueryMsg::*;
 
     Ok(match msg {
         TokenInfo {} => to_json_binary(&query_token_info(deps)?)?,
         Balance { address } => to_json_binary(&query_balance(deps, address)?)?,
         Minter {} => to_json_binary(&query_minter(deps)?)?,
+        Allowance { owner, spender } => to_json_binary(&query_allowance(deps, owner, spender)?)?,
+        AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => to_json_binary(&query_owner_allowances(deps, owner, start_after, limit)?)?,
+        AllSpenderAllowances {
+            spender,
+            start_after,
+            limit,
+        } => to_json_binary(&query_spender_allowances(
+            deps,
+            spender,
+            start_after,
+            limit,
+        )?)?,
+        AllAccounts { start_after, limit } => {
+            to_json_binary(&query_all_accounts(deps, start_after, limit)?)?
+        }
+        HolderCount {} => to_json_binary(&HolderCountResponse {
+            count: HOLDER_COUNT.load(deps.storage)?,
+        })?,
+        BalanceAt { address, height } => {
+            let address = deps.api.addr_validate(&address)?;
+            let balance = BALANCE_SNAPSHOTS
+                .may_load_at_height(deps.storage, &address, height)?
+                .unwrap_or_default();
+            to_json_binary(&BalanceResponse { balance })?
+        }
+        TotalSupplyAt { height } => to_json_binary(&TotalSupplyAtResponse {
+            total_supply: TOTAL_SUPPLY_SNAPSHOTS
+                .may_load_at_height(deps.storage, height)?
+                .unwrap_or_default(),
+        })?,
+        Hooks {} => to_json_binary(&query_hooks(deps)?)?,
+        Minters {} => to_json_binary(&query_minters(deps)?)?,
+        MinterAllowance { minter } => to_json_binary(&query_minter_allowance(deps, env, minter)?)?,
+        FeeConfig {} => to_json_binary(&query_fee_config(deps)?)?,
+        PauseState {} => to_json_binary(&PauseStateResponse {
+            paused: PAUSED.load(deps.storage)?,
+            guardian: GUARDIAN.load(deps.storage)?,
+        })?,
+        IsFrozen { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_json_binary(&IsFrozenResponse {
+                frozen: FROZEN.has(deps.storage, &address),
+            })?
+        }
+        FrozenAccounts { start_after, limit } => {
+            to_json_binary(&query_frozen_accounts(deps, start_after, limit)?)?
+        }
+        VestingInfo { address } => to_json_binary(&query_vesting_info(deps, env, address)?)?,
+        MaxBatchSize {} => to_json_binary(&MaxBatchSizeResponse {
+            max_batch_size: MAX_BATCH_SIZE.load(deps.storage)?,
+        })?,
+        PermitNonce { owner } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            to_json_binary(&PermitNonceResponse {
+                nonce: PERMIT_NONCES
+                    .may_load(deps.storage, &owner)?
+                    .unwrap_or_default(),
+            })?
+        }
+        MarketingInfo {} => to_json_binary(&query_marketing_info(deps)?)?,
+        DownloadLogo {} => to_json_binary(&query_download_logo(deps)?)?,
     })
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
@@ -63,27 +315,845 @@ pub fn execute(
     use ExecuteMsg::*;
 
     Ok(match msg {
-        Transfer { recipient, amount } => execute_transfer(deps, env, info, recipient, amount)?,
-        Burn { amount } => execute_burn(deps, env, info, amount)?,
+        Transfer { recipient, amount } => transfer_with_fee(
+            deps,
+            env,
+            info,
+            None,
+            recipient.clone(),
+            amount,
+            |deps, env, info, amount| execute_transfer(deps, env, info, recipient, amount),
+        )?,
+        Burn { amount } => {
+            let accounts = [info.sender.to_string()];
+            track_balances(deps, env.block.clone(), &accounts, |deps| {
+                execute_burn(deps, env, info, amount)
+            })?
+        }
         Send {
             contract,
             amount,
             msg,
-        } => execute_send(deps, env, info, contract, amount, msg)?,
-        Mint { recipient, amount } => execute_mint(deps, env, info, recipient, amount)?,
+        } => transfer_with_fee(
+            deps,
+            env,
+            info,
+            None,
+            contract.clone(),
+            amount,
+            |deps, env, info, amount| execute_send(deps, env, info, contract, amount, msg),
+        )?,
+        Mint { recipient, amount } => {
+            consume_minter_quota(deps.branch(), &env, &info.sender, amount)?;
+            let accounts = [recipient.clone()];
+            track_balances(deps, env.block.clone(), &accounts, |deps| {
+                mint(deps, env, info, recipient, amount)
+            })?
+        }
         UpdateMinter { new_minter } => execute_update_minter(deps, env, info, new_minter)?,
+        IncreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => execute_increase_allowance(deps, env, info, spender, amount, expires)?,
+        DecreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => execute_decrease_allowance(deps, env, info, spender, amount, expires)?,
+        TransferFrom {
+            owner,
+            recipient,
+            amount,
+        } => transfer_with_fee(
+            deps,
+            env,
+            info,
+            Some(owner.clone()),
+            recipient.clone(),
+            amount,
+            |deps, env, info, amount| {
+                execute_transfer_from(deps, env, info, owner, recipient, amount)
+            },
+        )?,
+        SendFrom {
+            owner,
+            contract,
+            amount,
+            msg,
+        } => transfer_with_fee(
+            deps,
+            env,
+            info,
+            Some(owner.clone()),
+            contract.clone(),
+            amount,
+            |deps, env, info, amount| {
+                execute_send_from(deps, env, info, owner, contract, amount, msg)
+            },
+        )?,
+        BurnFrom { owner, amount } => {
+            let accounts = [owner.clone()];
+            track_balances(deps, env.block.clone(), &accounts, |deps| {
+                execute_burn_from(deps, env, info, owner, amount)
+            })?
+        }
+        UpdateMarketing {
+            project,
+            description,
+            marketing,
+        } => execute_update_marketing(deps, env, info, project, description, marketing)?,
+        UploadLogo(logo) => execute_upload_logo(deps, env, info, logo)?,
+        AddHook {
+            contract,
+            on_failure,
+        } => add_hook(deps, info, contract, on_failure)?,
+        RemoveHook { contract } => remove_hook(deps, info, contract)?,
+        Permit(msg) => permit(deps, env, msg)?,
+        BatchTransfer { transfers } => batch_transfer(deps, env, info, transfers)?,
+        BatchMint { mints } => batch_mint(deps, env, info, mints)?,
+        SetMinterQuota {
+            minter,
+            quota,
+            window,
+        } => set_minter_quota(deps, env, info, minter, quota, window)?,
+        RemoveMinter { minter } => remove_minter(deps, info, minter)?,
+        UpdateTransferFee(transfer_fee) => {
+            ensure_admin(deps.as_ref(), &info.sender)?;
+            let fee_config = validate_transfer_fee(deps.as_ref(), transfer_fee)?;
+            FEE_CONFIG.save(deps.storage, &fee_config)?;
+            Response::new()
+                .add_attribute("action", "update_transfer_fee")
+                .add_attribute("fee_bps", fee_config.fee_bps.to_string())
+                .add_attribute("collector", fee_config.collector)
+        }
+        UpdateFeeExemptions { add, remove } => update_fee_exemptions(deps, info, add, remove)?,
+        Pause {} => set_paused(deps, info, true)?,
+        Unpause {} => set_paused(deps, info, false)?,
+        UpdateGuardian { guardian } => {
+            ensure_admin(deps.as_ref(), &info.sender)?;
+            GUARDIAN.save(deps.storage, &deps.api.addr_validate(&guardian)?)?;
+            Response::new()
+                .add_attribute("action", "update_guardian")
+                .add_attribute("guardian", guardian)
+        }
+        Freeze { address } => freeze(deps, info, address, true)?,
+        Unfreeze { address } => freeze(deps, info, address, false)?,
+        MintVesting {
+            recipient,
+            amount,
+            vesting,
+        } => {
+            consume_minter_quota(deps.branch(), &env, &info.sender, amount)?;
+            let recipient_addr = deps.api.addr_validate(&recipient)?;
+            let block = env.block.clone();
+            let accounts = [recipient.clone()];
+            let res = track_balances(deps.branch(), env.block.clone(), &accounts, |deps| {
+                mint(deps, env, info, recipient, amount)
+            })?;
+            let schedule = VestingSchedule {
+                amount,
+                curve: vesting,
+            };
+            add_vesting_schedule(deps, &block, &recipient_addr, schedule)?;
+            res
+        }
+        UpdateMaxBatchSize { max_batch_size } => {
+            ensure_admin(deps.as_ref(), &info.sender)?;
+            MAX_BATCH_SIZE.save(deps.storage, &max_batch_size)?;
+            Response::new()
+                .add_attribute("action", "update_max_batch_size")
+                .add_attribute("max_batch_size", max_batch_size.to_string())
+        }
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        // Only hooks registered with `HookFailurePolicy::Ignore` reply, and only on error
+        IGNORED_HOOK_FAILURE_REPLY_ID => {
+            Ok(Response::new().add_attribute("action", "ignored_hook_failure"))
+        }
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+fn query_hooks(deps: Deps) -> StdResult<HooksResponse> {
+    let hooks = HOOKS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            item.map(|(contract, on_failure)| HookInfo {
+                contract,
+                on_failure,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(HooksResponse {
+        admin: ADMIN.load(deps.storage)?,
+        hooks,
+    })
+}
+
+fn add_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+    on_failure: HookFailurePolicy,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    let contract_addr = deps.api.addr_validate(&contract)?;
+    if HOOKS.has(deps.storage, &contract_addr) {
+        return Err(ContractError::HookAlreadyRegistered { contract });
+    }
+    let count = HOOKS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .count();
+    if count >= MAX_HOOKS {
+        return Err(ContractError::TooManyHooks { max: MAX_HOOKS });
+    }
+    HOOKS.save(deps.storage, &contract_addr, &on_failure)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_hook")
+        .add_attribute("contract", contract))
+}
+
+fn remove_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    let contract_addr = deps.api.addr_validate(&contract)?;
+    if !HOOKS.has(deps.storage, &contract_addr) {
+        return Err(ContractError::HookNotRegistered { contract });
+    }
+    HOOKS.remove(deps.storage, &contract_addr);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_hook")
+        .add_attribute("contract", contract))
+}
+
+fn permit(deps: DepsMut, env: Env, msg: PermitMsg) -> Result<Response, ContractError> {
+    let PermitMsg {
+        owner,
+        spender,
+        amount,
+        expires,
+        nonce,
+        signature,
+        pubkey,
+    } = msg;
+
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    if owner_addr == spender_addr {
+        return Err(CW20BaseError::CannotSetOwnAccount {}.into());
+    }
+
+    let expected = PERMIT_NONCES
+        .may_load(deps.storage, &owner_addr)?
+        .unwrap_or_default();
+    if nonce != expected {
+        return Err(ContractError::InvalidPermitNonce { expected });
+    }
+    if expires.is_some_and(|expires| expires.is_expired(&env.block)) {
+        return Err(ContractError::PermitExpired);
+    }
+
+    let sign_doc = PermitSignDoc {
+        domain: PERMIT_DOMAIN.into(),
+        chain_id: env.block.chain_id,
+        contract: env.contract.address.into(),
+        owner: owner.clone(),
+        spender: spender.clone(),
+        amount,
+        expires,
+        nonce,
+    };
+    let hash = Sha256::digest(sign_doc.to_sign_bytes()?);
+    let valid = deps
+        .api
+        .secp256k1_verify(&hash, &signature, &pubkey)
+        .map_err(|_| ContractError::InvalidPermitSignature)?;
+    if !valid {
+        return Err(ContractError::InvalidPermitSignature);
+    }
+
+    // Cosmos SDK account addresses are ripemd160(sha256(compressed pubkey))
+    let signer = CanonicalAddr::from(Ripemd160::digest(Sha256::digest(&pubkey)).as_slice());
+    if deps.api.addr_humanize(&signer)? != owner_addr {
+        return Err(ContractError::PermitSignerMismatch);
+    }
+
+    PERMIT_NONCES.save(deps.storage, &owner_addr, &(nonce + 1))?;
+
+    if amount.is_zero() {
+        ALLOWANCES.remove(deps.storage, (&owner_addr, &spender_addr));
+        ALLOWANCES_SPENDER.remove(deps.storage, (&spender_addr, &owner_addr));
+    } else {
+        let allowance = AllowanceResponse {
+            allowance: amount,
+            expires: expires.unwrap_or_default(),
+        };
+        ALLOWANCES.save(deps.storage, (&owner_addr, &spender_addr), &allowance)?;
+        ALLOWANCES_SPENDER.save(deps.storage, (&spender_addr, &owner_addr), &allowance)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "permit")
+        .add_attribute("owner", owner)
+        .add_attribute("spender", spender)
+        .add_attribute("amount", amount)
+        .add_attribute("nonce", nonce.to_string()))
+}
+
+fn batch_transfer(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfers: Vec<Cw20Coin>,
+) -> Result<Response, ContractError> {
+    ensure_batch_size(deps.as_ref(), transfers.len())?;
+
+    let mut res = Response::new().add_attribute("action", "batch_transfer");
+    for Cw20Coin { address, amount } in transfers {
+        let leg = transfer_with_fee(
+            deps.branch(),
+            env.clone(),
+            info.clone(),
+            None,
+            address.clone(),
+            amount,
+            |deps, env, info, amount| execute_transfer(deps, env, info, address, amount),
+        )?;
+        res = add_batch_leg(res, "batch_transfer", leg);
+    }
+    Ok(res)
+}
+
+fn batch_mint(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    mints: Vec<Cw20Coin>,
+) -> Result<Response, ContractError> {
+    ensure_batch_size(deps.as_ref(), mints.len())?;
+
+    let mut res = Response::new().add_attribute("action", "batch_mint");
+    for Cw20Coin { address, amount } in mints {
+        consume_minter_quota(deps.branch(), &env, &info.sender, amount)?;
+        let accounts = [address.clone()];
+        let leg = track_balances(deps.branch(), env.block.clone(), &accounts, |deps| {
+            mint(deps, env.clone(), info.clone(), address, amount)
+        })?;
+        res = add_batch_leg(res, "batch_mint", leg);
+    }
+    Ok(res)
+}
+
+/// Mints for the cw20 minter or, once `consume_minter_quota` accepted the amount,
+/// for a minter with a quota
+fn mint(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, CW20BaseError> {
+    if !MINTERS.has(deps.storage, &info.sender) {
+        return execute_mint(deps, env, info, recipient, amount);
+    }
+
+    // Same as cw20-base `execute_mint`, without the minter check
+    let mut config = TOKEN_INFO.load(deps.storage)?;
+    config.total_supply += amount;
+    if let Some(limit) = config.get_cap() {
+        if config.total_supply > limit {
+            return Err(CW20BaseError::CannotExceedCap {});
+        }
+    }
+    TOKEN_INFO.save(deps.storage, &config)?;
+
+    let rcpt_addr = deps.api.addr_validate(&recipient)?;
+    BALANCES.update(
+        deps.storage,
+        &rcpt_addr,
+        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "mint")
+        .add_attribute("to", recipient)
+        .add_attribute("amount", amount)
+        .add_attribute("minter", info.sender))
+}
+
+/// Counts `amount` against the quota of `minter`, if it has one
+fn consume_minter_quota(
+    deps: DepsMut,
+    env: &Env,
+    minter: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let Some(mut quota) = MINTERS.may_load(deps.storage, minter)? else {
+        return Ok(());
+    };
+    quota.settle(env.block.time);
+    if amount > quota.remaining() {
+        return Err(ContractError::MinterQuotaExceeded {
+            remaining: quota.remaining(),
+        });
+    }
+    quota.minted += amount;
+    MINTERS.save(deps.storage, minter, &quota)?;
+    Ok(())
+}
+
+fn set_minter_quota(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    minter: String,
+    quota: Uint128,
+    window: u64,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+    // A zero window would reset the quota before every mint
+    if window == 0 {
+        return Err(ContractError::ZeroMinterQuotaWindow);
+    }
+
+    let minter_addr = deps.api.addr_validate(&minter)?;
+    let minter_quota = match MINTERS.may_load(deps.storage, &minter_addr)? {
+        // What was minted in the current window still counts
+        Some(mut minter_quota) if minter_quota.window == window => {
+            minter_quota.settle(env.block.time);
+            minter_quota.quota = quota;
+            minter_quota
+        }
+        _ => MinterQuota::new(quota, window, env.block.time),
+    };
+    MINTERS.save(deps.storage, &minter_addr, &minter_quota)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_minter_quota")
+        .add_attribute("minter", minter)
+        .add_attribute("quota", quota)
+        .add_attribute("window", window.to_string()))
+}
+
+fn remove_minter(
+    deps: DepsMut,
+    info: MessageInfo,
+    minter: String,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    let minter_addr = deps.api.addr_validate(&minter)?;
+    if !MINTERS.has(deps.storage, &minter_addr) {
+        return Err(ContractError::MinterNotRegistered { minter });
+    }
+    MINTERS.remove(deps.storage, &minter_addr);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_minter")
+        .add_attribute("minter", minter))
+}
+
+fn query_minters(deps: Deps) -> StdResult<MintersResponse> {
+    let minters = MINTERS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            item.map(|(minter, quota)| MinterInfo {
+                minter,
+                quota: quota.quota,
+                window: quota.window,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(MintersResponse { minters })
+}
+
+fn query_minter_allowance(
+    deps: Deps,
+    env: Env,
+    minter: String,
+) -> Result<MinterAllowanceResponse, ContractError> {
+    let minter_addr = deps.api.addr_validate(&minter)?;
+    let mut quota = MINTERS
+        .may_load(deps.storage, &minter_addr)?
+        .ok_or(ContractError::MinterNotRegistered { minter })?;
+    quota.settle(env.block.time);
+    Ok(MinterAllowanceResponse {
+        remaining: quota.remaining(),
+        window_end: quota.window_end(),
+    })
+}
+
+fn set_paused(deps: DepsMut, info: MessageInfo, paused: bool) -> Result<Response, ContractError> {
+    if GUARDIAN.load(deps.storage)? != info.sender {
+        return Err(ContractError::Unauthorized);
+    }
+    PAUSED.save(deps.storage, &paused)?;
+
+    Ok(Response::new().add_attribute("action", if paused { "pause" } else { "unpause" }))
+}
+
+fn freeze(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    frozen: bool,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+    if !FREEZABLE.load(deps.storage)? {
+        return Err(ContractError::FreezeDisabled);
+    }
+
+    let addr = deps.api.addr_validate(&address)?;
+    if frozen {
+        FROZEN.save(deps.storage, &addr, &Empty {})?;
+    } else {
+        FROZEN.remove(deps.storage, &addr);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", if frozen { "freeze" } else { "unfreeze" })
+        .add_attribute("address", address))
+}
+
+fn query_frozen_accounts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<FrozenAccountsResponse> {
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let accounts = FROZEN
+        .keys(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+    Ok(FrozenAccountsResponse { accounts })
+}
+
+fn ensure_batch_size(deps: Deps, size: usize) -> Result<(), ContractError> {
+    if size == 0 {
+        return Err(ContractError::EmptyBatch);
+    }
+    let max = MAX_BATCH_SIZE.load(deps.storage)?;
+    if size > max as usize {
+        return Err(ContractError::BatchTooLarge { max });
+    }
+    Ok(())
+}
+
+/// Every leg gets its own event with the attributes cw20-base returned for it
+fn add_batch_leg(res: Response, event: &str, leg: Response) -> Response {
+    res.add_event(Event::new(event).add_attributes(leg.attributes))
+        .add_submessages(leg.messages)
+}
+
+fn ensure_admin(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    if ADMIN.load(deps.storage)? != sender {
+        return Err(ContractError::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Moves `amount` out of `owner` (the sender, or the allowance owner when given) with
+/// `transfer`, minus the transfer fee which is sent to the fee collector
+fn transfer_with_fee<F>(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Option<String>,
+    recipient: String,
+    amount: Uint128,
+    transfer: F,
+) -> Result<Response, ContractError>
+where
+    F: FnOnce(DepsMut, Env, MessageInfo, Uint128) -> Result<Response, CW20BaseError>,
+{
+    let from = owner.clone().unwrap_or_else(|| info.sender.to_string());
+    let FeeConfig { fee_bps, collector } = FEE_CONFIG.load(deps.storage)?;
+    let exempt = [&from, &recipient].into_iter().any(|account| {
+        collector.as_str() == account.as_str()
+            || FEE_EXEMPT.has(deps.storage, &Addr::unchecked(account))
+    });
+    let fee = if exempt {
+        Uint128::zero()
+    } else {
+        amount.multiply_ratio(fee_bps, 10_000u128)
+    };
+
+    let mut accounts = vec![from, recipient];
+    if !fee.is_zero() {
+        accounts.push(collector.to_string());
+    }
+    track_balances(deps, env.block.clone(), &accounts, |mut deps| {
+        let res = transfer(deps.branch(), env.clone(), info.clone(), amount - fee)?;
+        if fee.is_zero() {
+            return Ok(res);
+        }
+        let collector = collector.to_string();
+        match owner {
+            None => execute_transfer(deps, env, info, collector.clone(), fee)?,
+            Some(owner) => execute_transfer_from(deps, env, info, owner, collector.clone(), fee)?,
+        };
+        Ok(res
+            .add_attribute("transfer_fee", fee)
+            .add_attribute("fee_recipient", collector))
+    })
+}
+
+fn validate_transfer_fee(
+    deps: Deps,
+    transfer_fee: TransferFee,
+) -> Result<FeeConfig, ContractError> {
+    if transfer_fee.fee_bps > MAX_TRANSFER_FEE_BPS {
+        return Err(ContractError::TransferFeeTooHigh {
+            max: MAX_TRANSFER_FEE_BPS,
+        });
+    }
+    Ok(FeeConfig {
+        fee_bps: transfer_fee.fee_bps,
+        collector: deps.api.addr_validate(&transfer_fee.collector)?,
+    })
+}
+
+fn update_fee_exemptions(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    for address in &add {
+        let addr = deps.api.addr_validate(address)?;
+        FEE_EXEMPT.save(deps.storage, &addr, &Empty {})?;
+    }
+    for address in &remove {
+        let addr = deps.api.addr_validate(address)?;
+        FEE_EXEMPT.remove(deps.storage, &addr);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_fee_exemptions")
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string()))
+}
+
+fn query_fee_config(deps: Deps) -> StdResult<FeeConfigResponse> {
+    let FeeConfig { fee_bps, collector } = FEE_CONFIG.load(deps.storage)?;
+    let exempt = FEE_EXEMPT
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    Ok(FeeConfigResponse {
+        fee_bps,
+        collector,
+        exempt,
+    })
+}
+
+/// Runs a cw20-base balance change and keeps the local state derived from balances
+/// (`HOLDER_COUNT` and the snapshots) in sync for the accounts it touches.
+/// The cw20-base attributes (`action`, `from`, `to`, `amount`) are kept, a missing `from`
+/// or `to` is added with an empty value and `new_supply` is added, so every balance change
+/// reports the same fields.
+/// Every changed balance is reported to the hook contracts before any message of the call,
+/// so they see the change before a `Send` receiver reacts to it.
+fn track_balances<F>(
+    mut deps: DepsMut,
+    block: BlockInfo,
+    accounts: &[String],
+    execute: F,
+) -> Result<Response, ContractError>
+where
+    F: FnOnce(DepsMut) -> Result<Response, CW20BaseError>,
+{
+    let mut accounts: Vec<Addr> = accounts.iter().map(Addr::unchecked).collect();
+    accounts.sort();
+    accounts.dedup();
+
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::Paused);
+    }
+    if let Some(frozen) = accounts
+        .iter()
+        .find(|account| FROZEN.has(deps.storage, account))
+    {
+        return Err(ContractError::AccountFrozen {
+            address: frozen.to_string(),
+        });
+    }
+
+    let balances_before = accounts
+        .iter()
+        .map(|account| load_balance(deps.as_ref(), account))
+        .collect::<StdResult<Vec<_>>>()?;
+    let total_supply_before = TOKEN_INFO.load(deps.storage)?.total_supply;
+
+    let mut res = execute(deps.branch())?;
+
+    let hooks = HOOKS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut hook_msgs = vec![];
+    let mut holders = HOLDER_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    for (account, before) in accounts.iter().zip(balances_before) {
+        let after = load_balance(deps.as_ref(), account)?;
+        if before == after {
+            continue;
+        }
+        if after < before {
+            ensure_unlocked(deps.as_ref(), &block, account, after)?;
+        }
+        match (before.is_zero(), after.is_zero()) {
+            (true, false) => holders += 1,
+            (false, true) => holders -= 1,
+            _ => {}
+        }
+        BALANCE_SNAPSHOTS.save(deps.storage, account, &after, block.height)?;
+
+        for (hook, on_failure) in &hooks {
+            let msg = BalanceChangedHookMsg {
+                address: account.to_string(),
+                old: before,
+                new: after,
+            }
+            .into_cosmos_msg(hook)?;
+            hook_msgs.push(match on_failure {
+                HookFailurePolicy::Revert => SubMsg::new(msg),
+                HookFailurePolicy::Ignore => {
+                    SubMsg::reply_on_error(msg, IGNORED_HOOK_FAILURE_REPLY_ID)
+                }
+            });
+        }
+    }
+    HOLDER_COUNT.save(deps.storage, &holders)?;
+
+    let total_supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    if total_supply != total_supply_before {
+        TOTAL_SUPPLY_SNAPSHOTS.save(deps.storage, &total_supply, block.height)?;
+    }
+
+    // Mints have no sender and burns no recipient, the keys are still there for indexers
+    for (key, after) in [("from", "action"), ("to", "from")] {
+        if !res.attributes.iter().any(|attr| attr.key == key) {
+            let index = res
+                .attributes
+                .iter()
+                .position(|attr| attr.key == after)
+                .map_or(0, |index| index + 1);
+            res.attributes.insert(index, attr(key, ""));
+        }
+    }
+
+    res.messages.splice(0..0, hook_msgs);
+    Ok(res.add_attribute("new_supply", total_supply))
+}
+
+/// Fails if `balance` no longer covers what vesting still locks
+fn ensure_unlocked(
+    deps: Deps,
+    block: &BlockInfo,
+    account: &Addr,
+    balance: Uint128,
+) -> Result<(), ContractError> {
+    let locked = locked_amount(deps, block, account)?;
+    if balance < locked {
+        return Err(ContractError::LockedByVesting {
+            address: account.to_string(),
+            locked,
+        });
+    }
+    Ok(())
+}
+
+fn locked_amount(deps: Deps, block: &BlockInfo, account: &Addr) -> StdResult<Uint128> {
+    Ok(VESTING
+        .may_load(deps.storage, account)?
+        .unwrap_or_default()
+        .iter()
+        .map(|schedule| schedule.locked(block.time))
+        .sum())
+}
+
+fn add_vesting_schedule(
+    deps: DepsMut,
+    block: &BlockInfo,
+    account: &Addr,
+    schedule: VestingSchedule,
+) -> Result<(), ContractError> {
+    schedule
+        .validate()
+        .map_err(|reason| ContractError::InvalidVestingSchedule {
+            reason: reason.into(),
+        })?;
+
+    let mut schedules = VESTING.may_load(deps.storage, account)?.unwrap_or_default();
+    schedules.retain(|schedule| !schedule.locked(block.time).is_zero());
+    if schedules.len() >= MAX_VESTING_SCHEDULES {
+        return Err(ContractError::TooManyVestingSchedules {
+            max: MAX_VESTING_SCHEDULES,
+        });
+    }
+    schedules.push(schedule);
+    VESTING.save(deps.storage, account, &schedules)?;
+    Ok(())
+}
+
+fn query_vesting_info(deps: Deps, env: Env, address: String) -> StdResult<VestingInfoResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let schedules = VESTING
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let locked = locked_amount(deps, &env.block, &address)?;
+    let balance = load_balance(deps, &address)?;
+    Ok(VestingInfoResponse {
+        locked,
+        unlocked: balance.saturating_sub(locked),
+        schedules,
     })
 }
 
+fn load_balance(deps: Deps, account: &Addr) -> StdResult<Uint128> {
+    Ok(BALANCES
+        .may_load(deps.storage, account)?
+        .unwrap_or_default())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::error::CW20BaseError;
+    use crate::state::{VestingCurve, VestingStep};
+    use cosmwasm_std::attr;
     use cosmwasm_std::{
         coins, from_json,
         testing::{mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info},
         CosmosMsg, StdError, StdResult, SubMsg, Uint128, WasmMsg,
     };
-    use cw20::{BalanceResponse, Cw20Coin, Cw20ReceiveMsg, MinterResponse, TokenInfoResponse};
+    use cw20::{
+        AllAccountsResponse, AllAllowancesResponse, AllSpenderAllowancesResponse,
+        AllowanceResponse, BalanceResponse, Cw20Coin, Cw20ReceiveMsg, DownloadLogoResponse,
+        EmbeddedLogo, Logo, LogoInfo, MarketingInfoResponse, MinterResponse, TokenInfoResponse,
+    };
+    use cw20_base::msg::InstantiateMarketingInfo;
+    use k256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey};
 
     use super::*;
 
@@ -139,6 +1209,7 @@ mod tests {
                 amount,
             }],
             mint: mint.clone(),
+            ..Default::default()
         };
         let info = mock_info("creator", &[]);
         let env = mock_env();
@@ -189,6 +1260,7 @@ mod tests {
                 },
             ],
             mint: None,
+            ..Default::default()
         };
         let err =
             instantiate(deps.as_mut(), env.clone(), info.clone(), instantiate_msg).unwrap_err();
@@ -210,6 +1282,7 @@ mod tests {
                 },
             ],
             mint: None,
+            ..Default::default()
         };
         let res = instantiate(deps.as_mut(), env, info, instantiate_msg).unwrap();
         assert_eq!(0, res.messages.len());
@@ -590,4 +1663,1548 @@ mod tests {
         let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
         assert_eq!(err, ContractError::CW20Base(CW20BaseError::Unauthorized {}));
     }
+
+    #[test]
+    fn allowances() {
+        let mut deps = mock_dependencies();
+        let owner = deps.api.addr_make("addr0001").to_string();
+        let spender = deps.api.addr_make("addr0002").to_string();
+        let recipient = deps.api.addr_make("addr0003").to_string();
+        let contract = deps.api.addr_make("contract0001").to_string();
+        let amount = Uint128::new(10000);
+        let env = mock_env();
+
+        do_instantiate(deps.as_mut(), &owner, amount);
+
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(5000),
+            expires: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&owner, &[]), msg).unwrap();
+        let msg = ExecuteMsg::DecreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(1000),
+            expires: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&owner, &[]), msg).unwrap();
+
+        let msg = QueryMsg::Allowance {
+            owner: owner.clone(),
+            spender: spender.clone(),
+        };
+        let allowance: AllowanceResponse =
+            from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(allowance.allowance, Uint128::new(4000));
+
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.clone(),
+            recipient: recipient.clone(),
+            amount: Uint128::new(1000),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&spender, &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::SendFrom {
+            owner: owner.clone(),
+            contract: contract.clone(),
+            amount: Uint128::new(1000),
+            msg: Binary::default(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(&spender, &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        let msg = ExecuteMsg::BurnFrom {
+            owner: owner.clone(),
+            amount: Uint128::new(1000),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&spender, &[]), msg).unwrap();
+
+        assert_eq!(get_balance(deps.as_ref(), &owner), Uint128::new(7000));
+        assert_eq!(get_balance(deps.as_ref(), &recipient), Uint128::new(1000));
+        assert_eq!(get_balance(deps.as_ref(), &contract), Uint128::new(1000));
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap().total_supply,
+            Uint128::new(9000)
+        );
+
+        // the allowance is used up
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.clone(),
+            recipient,
+            amount: Uint128::new(1001),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(&spender, &[]), msg).unwrap_err();
+        assert!(matches!(err, cw20_base_std_err!(StdError::Overflow { .. })));
+
+        let msg = QueryMsg::AllAllowances {
+            owner: owner.clone(),
+            start_after: None,
+            limit: None,
+        };
+        let allowances: AllAllowancesResponse =
+            from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(allowances.allowances.len(), 1);
+        assert_eq!(allowances.allowances[0].spender, spender);
+        assert_eq!(allowances.allowances[0].allowance, Uint128::new(1000));
+
+        let msg = QueryMsg::AllSpenderAllowances {
+            spender,
+            start_after: None,
+            limit: None,
+        };
+        let allowances: AllSpenderAllowancesResponse =
+            from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(allowances.allowances.len(), 1);
+        assert_eq!(allowances.allowances[0].owner, owner);
+    }
+
+    fn get_holder_count(deps: Deps) -> u64 {
+        let res: HolderCountResponse =
+            from_json(query(deps, mock_env(), QueryMsg::HolderCount {}).unwrap()).unwrap();
+        res.count
+    }
+
+    #[test]
+    fn holder_count_and_all_accounts() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let addr2 = deps.api.addr_make("addr0002").to_string();
+        let addr3 = deps.api.addr_make("addr0003").to_string();
+        let minter = deps.api.addr_make("minter").to_string();
+        let env = mock_env();
+
+        do_instantiate_with_minter(deps.as_mut(), &addr1, Uint128::new(1000), &minter, None);
+        assert_eq!(get_holder_count(deps.as_ref()), 1);
+
+        let msg = ExecuteMsg::Mint {
+            recipient: addr2.clone(),
+            amount: Uint128::new(500),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&minter, &[]), msg).unwrap();
+        assert_eq!(get_holder_count(deps.as_ref()), 2);
+
+        // transfer to an existing holder and to oneself doesn't change the count
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: Uint128::new(100),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr1.clone(),
+            amount: Uint128::new(100),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(get_holder_count(deps.as_ref()), 2);
+
+        // moving the whole balance replaces a holder
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr3.clone(),
+            amount: Uint128::new(900),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(get_holder_count(deps.as_ref()), 2);
+
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(600),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr2, &[]), msg).unwrap();
+        assert_eq!(get_holder_count(deps.as_ref()), 1);
+
+        // failed calls leave the count untouched
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(1),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr2, &[]), msg).unwrap_err();
+        assert_eq!(get_holder_count(deps.as_ref()), 1);
+
+        let msg = QueryMsg::AllAccounts {
+            start_after: None,
+            limit: None,
+        };
+        let res: AllAccountsResponse = from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        let mut expected = vec![addr1, addr2, addr3];
+        expected.sort();
+        assert_eq!(res.accounts, expected);
+    }
+
+    #[test]
+    fn instantiate_with_custom_metadata() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let info = mock_info("creator", &[]);
+        let env = mock_env();
+
+        let instantiate_msg = InstantiateMsg {
+            symbol: Some("W".into()),
+            ..Default::default()
+        };
+        let err =
+            instantiate(deps.as_mut(), env.clone(), info.clone(), instantiate_msg).unwrap_err();
+        assert!(matches!(
+            err,
+            cw20_base_std_err!(StdError::GenericErr { .. })
+        ));
+
+        let instantiate_msg = InstantiateMsg {
+            decimals: Some(19),
+            ..Default::default()
+        };
+        let err =
+            instantiate(deps.as_mut(), env.clone(), info.clone(), instantiate_msg).unwrap_err();
+        assert!(matches!(
+            err,
+            cw20_base_std_err!(StdError::GenericErr { .. })
+        ));
+
+        let instantiate_msg = InstantiateMsg {
+            name: Some("Test Wtoken".into()),
+            symbol: Some("TWTK".into()),
+            decimals: Some(18),
+            initial_balances: vec![Cw20Coin {
+                address: addr1,
+                amount: Uint128::new(100),
+            }],
+            mint: None,
+            marketing: None,
+            admin: None,
+            max_batch_size: None,
+            initial_vesting: None,
+            guardian: None,
+            transfer_fee: None,
+            freezable: None,
+        };
+        instantiate(deps.as_mut(), env, info, instantiate_msg).unwrap();
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap(),
+            TokenInfoResponse {
+                name: "Test Wtoken".into(),
+                symbol: "TWTK".into(),
+                decimals: 18,
+                total_supply: Uint128::new(100),
+            }
+        );
+    }
+
+    #[test]
+    fn marketing_and_logo() {
+        let mut deps = mock_dependencies();
+        let marketing = deps.api.addr_make("marketing").to_string();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let env = mock_env();
+
+        let instantiate_msg = InstantiateMsg {
+            marketing: Some(InstantiateMarketingInfo {
+                project: Some("Wtoken project".into()),
+                description: Some("Wtoken on PRYZM".into()),
+                marketing: Some(marketing.clone()),
+                logo: Some(Logo::Url("https://example.com/wtoken.png".into())),
+            }),
+            ..Default::default()
+        };
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            instantiate_msg,
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::UpdateMarketing {
+            project: None,
+            description: Some("Updated".into()),
+            marketing: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&addr1, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::CW20Base(CW20BaseError::Unauthorized {}));
+        execute(deps.as_mut(), env.clone(), mock_info(&marketing, &[]), msg).unwrap();
+
+        // png without the png header is rejected
+        let msg = ExecuteMsg::UploadLogo(Logo::Embedded(EmbeddedLogo::Png(
+            b"not a png".to_vec().into(),
+        )));
+        let err = execute(deps.as_mut(), env.clone(), mock_info(&marketing, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CW20Base(CW20BaseError::InvalidPngHeader {})
+        );
+
+        let svg = b"<?xml version=\"1.0\"?><svg></svg>".to_vec();
+        let msg = ExecuteMsg::UploadLogo(Logo::Embedded(EmbeddedLogo::Svg(svg.clone().into())));
+        execute(deps.as_mut(), env.clone(), mock_info(&marketing, &[]), msg).unwrap();
+
+        let info: MarketingInfoResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::MarketingInfo {}).unwrap())
+                .unwrap();
+        assert_eq!(
+            info,
+            MarketingInfoResponse {
+                project: Some("Wtoken project".into()),
+                description: Some("Updated".into()),
+                marketing: Some(Addr::unchecked(marketing)),
+                logo: Some(LogoInfo::Embedded),
+            }
+        );
+
+        let logo: DownloadLogoResponse =
+            from_json(query(deps.as_ref(), env, QueryMsg::DownloadLogo {}).unwrap()).unwrap();
+        assert_eq!(logo.mime_type, "image/svg+xml");
+        assert_eq!(logo.data, Binary::from(svg));
+    }
+
+    #[test]
+    fn balance_and_total_supply_at_height() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let addr2 = deps.api.addr_make("addr0002").to_string();
+        let minter = deps.api.addr_make("minter").to_string();
+        let mut env = mock_env();
+        let start = env.block.height;
+
+        do_instantiate_with_minter(deps.as_mut(), &addr1, Uint128::new(1000), &minter, None);
+
+        env.block.height = start + 10;
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: Uint128::new(300),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+
+        env.block.height = start + 20;
+        let msg = ExecuteMsg::Mint {
+            recipient: addr2.clone(),
+            amount: Uint128::new(200),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&minter, &[]), msg).unwrap();
+
+        let balance_at = |deps: Deps, address: &str, height: u64| -> Uint128 {
+            let msg = QueryMsg::BalanceAt {
+                address: address.to_string(),
+                height,
+            };
+            let res: BalanceResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.balance
+        };
+        let total_supply_at = |deps: Deps, height: u64| -> Uint128 {
+            let msg = QueryMsg::TotalSupplyAt { height };
+            let res: TotalSupplyAtResponse =
+                from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.total_supply
+        };
+
+        // a height reflects the state at the start of that block
+        assert_eq!(
+            balance_at(deps.as_ref(), &addr1, start + 1),
+            Uint128::new(1000)
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), &addr1, start + 10),
+            Uint128::new(1000)
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), &addr1, start + 11),
+            Uint128::new(700)
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), &addr2, start + 11),
+            Uint128::new(300)
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), &addr2, start + 21),
+            Uint128::new(500)
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), &addr2, start + 5),
+            Uint128::zero()
+        );
+
+        assert_eq!(
+            total_supply_at(deps.as_ref(), start + 1),
+            Uint128::new(1000)
+        );
+        assert_eq!(
+            total_supply_at(deps.as_ref(), start + 20),
+            Uint128::new(1000)
+        );
+        assert_eq!(
+            total_supply_at(deps.as_ref(), start + 21),
+            Uint128::new(1200)
+        );
+    }
+
+    #[test]
+    fn balance_changed_hooks() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let addr2 = deps.api.addr_make("addr0002").to_string();
+        let hook1 = deps.api.addr_make("hook0001").to_string();
+        let hook2 = deps.api.addr_make("hook0002").to_string();
+        let contract = deps.api.addr_make("contract0001").to_string();
+        let env = mock_env();
+
+        // "creator" instantiates and becomes the admin
+        let admin = "creator";
+        do_instantiate(deps.as_mut(), &addr1, Uint128::new(1000));
+
+        let msg = ExecuteMsg::AddHook {
+            contract: hook1.clone(),
+            on_failure: HookFailurePolicy::Revert,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&addr1, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(admin, &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), env.clone(), mock_info(admin, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::HookAlreadyRegistered {
+                contract: hook1.clone()
+            }
+        );
+        let msg = ExecuteMsg::AddHook {
+            contract: hook2.clone(),
+            on_failure: HookFailurePolicy::Ignore,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(admin, &[]), msg).unwrap();
+
+        let hook_msg = |address: &str, old: u128, new: u128, hook: &str| {
+            BalanceChangedHookMsg {
+                address: address.to_string(),
+                old: Uint128::new(old),
+                new: Uint128::new(new),
+            }
+            .into_cosmos_msg(hook)
+            .unwrap()
+        };
+
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: Uint128::new(100),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+        // one message per changed balance and hook
+        assert_eq!(res.messages.len(), 4);
+        for (address, old, new) in [(&addr1, 1000, 900), (&addr2, 0, 100)] {
+            assert!(res
+                .messages
+                .contains(&SubMsg::new(hook_msg(address, old, new, &hook1))));
+            assert!(res.messages.contains(&SubMsg::reply_on_error(
+                hook_msg(address, old, new, &hook2),
+                IGNORED_HOOK_FAILURE_REPLY_ID,
+            )));
+        }
+
+        // hooks are notified before the receiver of a send
+        let msg = ExecuteMsg::RemoveHook {
+            contract: hook2.clone(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(admin, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Send {
+            contract: contract.clone(),
+            amount: Uint128::new(100),
+            msg: Binary::default(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(&addr2, &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        assert!(res.messages[..2].contains(&SubMsg::new(hook_msg(&addr2, 100, 0, &hook1))));
+        assert!(res.messages[..2].contains(&SubMsg::new(hook_msg(&contract, 0, 100, &hook1))));
+
+        let hooks: HooksResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::Hooks {}).unwrap()).unwrap();
+        assert_eq!(
+            hooks,
+            HooksResponse {
+                admin: Addr::unchecked(admin),
+                hooks: vec![HookInfo {
+                    contract: Addr::unchecked(hook1),
+                    on_failure: HookFailurePolicy::Revert,
+                }],
+            }
+        );
+
+        let msg = ExecuteMsg::RemoveHook {
+            contract: hook2.clone(),
+        };
+        let err = execute(deps.as_mut(), env, mock_info(admin, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::HookNotRegistered { contract: hook2 });
+    }
+
+    #[test]
+    fn hooks_are_bounded() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        do_instantiate(deps.as_mut(), &addr1, Uint128::new(1000));
+
+        for i in 0..MAX_HOOKS {
+            let msg = ExecuteMsg::AddHook {
+                contract: deps.api.addr_make(&format!("hook{i}")).to_string(),
+                on_failure: HookFailurePolicy::Revert,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::AddHook {
+            contract: deps.api.addr_make("one-too-many").to_string(),
+            on_failure: HookFailurePolicy::Revert,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::TooManyHooks { max: MAX_HOOKS });
+    }
+
+    struct PermitSigner {
+        key: SigningKey,
+        pubkey: Binary,
+        address: String,
+    }
+
+    impl PermitSigner {
+        fn new(deps: Deps, seed: u8) -> Self {
+            let key = SigningKey::from_slice(&[seed; 32]).unwrap();
+            let pubkey = Binary::from(key.verifying_key().to_encoded_point(true).as_bytes());
+            let raw = Ripemd160::digest(Sha256::digest(&pubkey));
+            let address = deps
+                .api
+                .addr_humanize(&CanonicalAddr::from(raw.as_slice()))
+                .unwrap()
+                .to_string();
+            PermitSigner {
+                key,
+                pubkey,
+                address,
+            }
+        }
+
+        fn sign(&self, env: &Env, spender: &str, amount: u128, nonce: u64) -> PermitMsg {
+            let sign_doc = PermitSignDoc {
+                domain: PERMIT_DOMAIN.into(),
+                chain_id: env.block.chain_id.clone(),
+                contract: env.contract.address.to_string(),
+                owner: self.address.clone(),
+                spender: spender.into(),
+                amount: Uint128::new(amount),
+                expires: None,
+                nonce,
+            };
+            let hash = Sha256::digest(sign_doc.to_sign_bytes().unwrap());
+            let signature: Signature = self.key.sign_prehash(&hash).unwrap();
+            PermitMsg {
+                owner: self.address.clone(),
+                spender: spender.into(),
+                amount: Uint128::new(amount),
+                expires: None,
+                nonce,
+                signature: Binary::from(signature.to_bytes().as_slice()),
+                pubkey: self.pubkey.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn permit() {
+        let mut deps = mock_dependencies();
+        let owner = PermitSigner::new(deps.as_ref(), 1);
+        let other = PermitSigner::new(deps.as_ref(), 2);
+        let spender = deps.api.addr_make("spender").to_string();
+        let relayer = deps.api.addr_make("relayer").to_string();
+        let recipient = deps.api.addr_make("recipient").to_string();
+        let env = mock_env();
+
+        do_instantiate(deps.as_mut(), &owner.address, Uint128::new(1000));
+
+        // signed by another key
+        let mut msg = owner.sign(&env, &spender, 500, 0);
+        msg.pubkey = other.pubkey.clone();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&relayer, &[]),
+            ExecuteMsg::Permit(msg),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidPermitSignature);
+
+        // valid signature of another account over the owner's permit
+        let impostor = PermitSigner {
+            key: other.key.clone(),
+            pubkey: other.pubkey.clone(),
+            address: owner.address.clone(),
+        };
+        let msg = impostor.sign(&env, &spender, 500, 0);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&relayer, &[]),
+            ExecuteMsg::Permit(msg),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PermitSignerMismatch);
+
+        // signed for another chain
+        let mut other_chain = env.clone();
+        other_chain.block.chain_id = "other-chain".into();
+        let msg = owner.sign(&other_chain, &spender, 500, 0);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&relayer, &[]),
+            ExecuteMsg::Permit(msg),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidPermitSignature);
+
+        let msg = owner.sign(&env, &spender, 500, 0);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&relayer, &[]),
+            ExecuteMsg::Permit(msg.clone()),
+        )
+        .unwrap();
+        assert_eq!(
+            query_allowance(deps.as_ref(), owner.address.clone(), spender.clone())
+                .unwrap()
+                .allowance,
+            Uint128::new(500)
+        );
+
+        // a permit can't be replayed
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&relayer, &[]),
+            ExecuteMsg::Permit(msg),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidPermitNonce { expected: 1 });
+        let nonce: PermitNonceResponse = from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::PermitNonce {
+                    owner: owner.address.clone(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(nonce.nonce, 1);
+
+        // the next permit sets the allowance rather than adding to it
+        let msg = owner.sign(&env, &spender, 200, 1);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&relayer, &[]),
+            ExecuteMsg::Permit(msg),
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.address.clone(),
+            recipient: recipient.clone(),
+            amount: Uint128::new(200),
+        };
+        execute(deps.as_mut(), env, mock_info(&spender, &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &recipient), Uint128::new(200));
+        assert_eq!(
+            query_allowance(deps.as_ref(), owner.address, spender)
+                .unwrap()
+                .allowance,
+            Uint128::zero()
+        );
+    }
+
+    #[test]
+    fn batch_transfer_and_mint() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let addr2 = deps.api.addr_make("addr0002").to_string();
+        let addr3 = deps.api.addr_make("addr0003").to_string();
+        let minter = deps.api.addr_make("minter").to_string();
+        let env = mock_env();
+        let coin = |address: &str, amount: u128| Cw20Coin {
+            address: address.to_string(),
+            amount: Uint128::new(amount),
+        };
+
+        do_instantiate_with_minter(deps.as_mut(), &addr1, Uint128::new(1000), &minter, None);
+
+        let msg = ExecuteMsg::BatchTransfer {
+            transfers: vec![coin(&addr2, 100), coin(&addr3, 200)],
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(res.events.len(), 2);
+        assert_eq!(res.events[1].ty, "batch_transfer");
+        assert!(res.events[1]
+            .attributes
+            .contains(&cosmwasm_std::attr("to", addr3.clone())));
+        assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(700));
+        assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(100));
+        assert_eq!(get_balance(deps.as_ref(), &addr3), Uint128::new(200));
+        assert_eq!(get_holder_count(deps.as_ref()), 3);
+
+        // any failing leg fails the whole batch
+        let msg = ExecuteMsg::BatchTransfer {
+            transfers: vec![coin(&addr2, 800), coin(&addr3, 200)],
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap_err();
+        assert!(matches!(err, cw20_base_std_err!(StdError::Overflow { .. })));
+
+        let msg = ExecuteMsg::BatchMint {
+            mints: vec![coin(&addr1, 10), coin(&addr2, 20)],
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&addr1, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::CW20Base(CW20BaseError::Unauthorized {}));
+        let res = execute(deps.as_mut(), env.clone(), mock_info(&minter, &[]), msg).unwrap();
+        assert_eq!(res.events.len(), 2);
+        assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(710));
+        assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(120));
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap().total_supply,
+            Uint128::new(1030)
+        );
+
+        let msg = ExecuteMsg::BatchMint { mints: vec![] };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(&minter, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::EmptyBatch);
+
+        let msg = ExecuteMsg::UpdateMaxBatchSize { max_batch_size: 1 };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&addr1, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::BatchTransfer {
+            transfers: vec![coin(&addr2, 1), coin(&addr3, 1)],
+        };
+        let err = execute(deps.as_mut(), env, mock_info(&addr1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::BatchTooLarge { max: 1 });
+    }
+
+    #[test]
+    fn minters_with_quotas() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let bridge = deps.api.addr_make("bridge").to_string();
+        let emissions = deps.api.addr_make("emissions").to_string();
+        let minter = deps.api.addr_make("minter").to_string();
+        let mut env = mock_env();
+        let cap = Uint128::new(2000);
+
+        do_instantiate_with_minter(
+            deps.as_mut(),
+            &addr1,
+            Uint128::new(1000),
+            &minter,
+            Some(cap),
+        );
+
+        let msg = ExecuteMsg::SetMinterQuota {
+            minter: bridge.clone(),
+            quota: Uint128::new(300),
+            window: 3600,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&addr1, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::SetMinterQuota {
+            minter: emissions.clone(),
+            quota: Uint128::new(1000),
+            window: 86400,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+
+        let mint_msg = |amount: u128| ExecuteMsg::Mint {
+            recipient: addr1.clone(),
+            amount: Uint128::new(amount),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&bridge, &[]),
+            mint_msg(200),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&bridge, &[]),
+            mint_msg(101),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::MinterQuotaExceeded {
+                remaining: Uint128::new(100)
+            }
+        );
+        // minters don't share quotas
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&emissions, &[]),
+            mint_msg(500),
+        )
+        .unwrap();
+
+        let allowance = |deps: Deps, env: &Env, minter: &str| -> MinterAllowanceResponse {
+            let msg = QueryMsg::MinterAllowance {
+                minter: minter.to_string(),
+            };
+            from_json(query(deps, env.clone(), msg).unwrap()).unwrap()
+        };
+        assert_eq!(
+            allowance(deps.as_ref(), &env, &bridge),
+            MinterAllowanceResponse {
+                remaining: Uint128::new(100),
+                window_end: env.block.time.plus_seconds(3600),
+            }
+        );
+
+        // the quota is available again in the next window
+        env.block.time = env.block.time.plus_seconds(3600 * 2 + 10);
+        assert_eq!(
+            allowance(deps.as_ref(), &env, &bridge).remaining,
+            Uint128::new(300)
+        );
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&bridge, &[]),
+            mint_msg(300),
+        )
+        .unwrap();
+
+        // the global cap still applies
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&emissions, &[]),
+            mint_msg(500),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CW20Base(CW20BaseError::CannotExceedCap {})
+        );
+        assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(2000));
+
+        let minters: MintersResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::Minters {}).unwrap()).unwrap();
+        assert_eq!(minters.minters.len(), 2);
+
+        let msg = ExecuteMsg::RemoveMinter {
+            minter: bridge.clone(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), env, mock_info(&bridge, &[]), mint_msg(1)).unwrap_err();
+        assert_eq!(err, ContractError::CW20Base(CW20BaseError::Unauthorized {}));
+    }
+
+    #[test]
+    fn minter_quota_window_cant_be_zero() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let bridge = deps.api.addr_make("bridge").to_string();
+        let minter = deps.api.addr_make("minter").to_string();
+
+        do_instantiate_with_minter(deps.as_mut(), &addr1, Uint128::new(1000), &minter, None);
+
+        let msg = ExecuteMsg::SetMinterQuota {
+            minter: bridge.clone(),
+            quota: Uint128::new(300),
+            window: 0,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::ZeroMinterQuotaWindow);
+
+        let minters: MintersResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::Minters {}).unwrap()).unwrap();
+        assert!(minters.minters.is_empty());
+    }
+
+    #[test]
+    fn vesting() {
+        let mut deps = mock_dependencies();
+        let team = deps.api.addr_make("team").to_string();
+        let investor = deps.api.addr_make("investor").to_string();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let minter = deps.api.addr_make("minter").to_string();
+        let mut env = mock_env();
+        let now = env.block.time;
+
+        let instantiate_msg = InstantiateMsg {
+            initial_balances: vec![Cw20Coin {
+                address: team.clone(),
+                amount: Uint128::new(100),
+            }],
+            mint: Some(MinterResponse {
+                minter: minter.clone(),
+                cap: None,
+            }),
+            initial_vesting: Some(vec![VestingCoin {
+                address: team.clone(),
+                amount: Uint128::new(1000),
+                vesting: VestingCurve::Linear {
+                    start: now,
+                    cliff: now.plus_seconds(100),
+                    end: now.plus_seconds(1000),
+                },
+            }]),
+            ..Default::default()
+        };
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            instantiate_msg,
+        )
+        .unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &team), Uint128::new(1100));
+
+        let vesting_info = |deps: Deps, env: &Env, address: &str| -> VestingInfoResponse {
+            let msg = QueryMsg::VestingInfo {
+                address: address.to_string(),
+            };
+            from_json(query(deps, env.clone(), msg).unwrap()).unwrap()
+        };
+        let info = vesting_info(deps.as_ref(), &env, &team);
+        assert_eq!(info.locked, Uint128::new(1000));
+        assert_eq!(info.unlocked, Uint128::new(100));
+
+        let transfer = |amount: u128| ExecuteMsg::Transfer {
+            recipient: addr1.clone(),
+            amount: Uint128::new(amount),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&team, &[]),
+            transfer(100),
+        )
+        .unwrap();
+
+        // nothing unlocks before the cliff, then linear from the start
+        env.block.time = now.plus_seconds(99);
+        assert_eq!(
+            vesting_info(deps.as_ref(), &env, &team).locked,
+            Uint128::new(1000)
+        );
+        env.block.time = now.plus_seconds(500);
+        assert_eq!(
+            vesting_info(deps.as_ref(), &env, &team).locked,
+            Uint128::new(500)
+        );
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&team, &[]),
+            transfer(500),
+        )
+        .unwrap();
+
+        // piecewise vesting on mint
+        let steps = vec![
+            VestingStep {
+                time: now.plus_seconds(600),
+                unlocked: Uint128::new(100),
+            },
+            VestingStep {
+                time: now.plus_seconds(800),
+                unlocked: Uint128::new(300),
+            },
+        ];
+        let msg = ExecuteMsg::MintVesting {
+            recipient: investor.clone(),
+            amount: Uint128::new(400),
+            vesting: VestingCurve::Piecewise {
+                steps: steps.clone(),
+            },
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(&minter, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidVestingSchedule { .. }));
+
+        let msg = ExecuteMsg::MintVesting {
+            recipient: investor.clone(),
+            amount: Uint128::new(300),
+            vesting: VestingCurve::Piecewise { steps },
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&addr1, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::CW20Base(CW20BaseError::Unauthorized {}));
+        execute(deps.as_mut(), env.clone(), mock_info(&minter, &[]), msg).unwrap();
+
+        let locked_at = |deps: Deps, env: &mut Env, seconds: u64| {
+            env.block.time = now.plus_seconds(seconds);
+            vesting_info(deps, env, &investor).locked
+        };
+        assert_eq!(locked_at(deps.as_ref(), &mut env, 599), Uint128::new(300));
+        assert_eq!(locked_at(deps.as_ref(), &mut env, 600), Uint128::new(200));
+        assert_eq!(locked_at(deps.as_ref(), &mut env, 700), Uint128::new(100));
+        assert_eq!(locked_at(deps.as_ref(), &mut env, 800), Uint128::zero());
+
+        // everything is transferable once vested
+        env.block.time = now.plus_seconds(1000);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&team, &[]),
+            transfer(500),
+        )
+        .unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &team), Uint128::zero());
+    }
+
+    #[test]
+    fn freeze_accounts() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let addr2 = deps.api.addr_make("addr0002").to_string();
+        let env = mock_env();
+
+        do_instantiate(deps.as_mut(), &addr1, Uint128::new(1000));
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: Uint128::new(100),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::Freeze {
+            address: addr2.clone(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&addr1, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+
+        let frozen = ContractError::AccountFrozen {
+            address: addr2.clone(),
+        };
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr1.clone(),
+            amount: Uint128::new(1),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(&addr2, &[]), msg).unwrap_err();
+        assert_eq!(err, frozen);
+        let msg = ExecuteMsg::Send {
+            contract: addr2.clone(),
+            amount: Uint128::new(1),
+            msg: Binary::default(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap_err();
+        assert_eq!(err, frozen);
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(1),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(&addr2, &[]), msg).unwrap_err();
+        assert_eq!(err, frozen);
+
+        let res: IsFrozenResponse = from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::IsFrozen {
+                    address: addr2.clone(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(res.frozen);
+        let msg = QueryMsg::FrozenAccounts {
+            start_after: None,
+            limit: None,
+        };
+        let res: FrozenAccountsResponse =
+            from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.accounts, vec![Addr::unchecked(&addr2)]);
+
+        let msg = ExecuteMsg::Unfreeze {
+            address: addr2.clone(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr1.clone(),
+            amount: Uint128::new(100),
+        };
+        execute(deps.as_mut(), env, mock_info(&addr2, &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(1000));
+    }
+
+    #[test]
+    fn freeze_can_be_disabled() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let instantiate_msg = InstantiateMsg {
+            freezable: Some(false),
+            ..Default::default()
+        };
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            instantiate_msg,
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::Freeze { address: addr1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::FreezeDisabled);
+    }
+
+    #[test]
+    fn guardian_pauses_transfers() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let addr2 = deps.api.addr_make("addr0002").to_string();
+        let guardian = deps.api.addr_make("guardian").to_string();
+        let minter = deps.api.addr_make("minter").to_string();
+        let env = mock_env();
+
+        do_instantiate_with_minter(deps.as_mut(), &addr1, Uint128::new(1000), &minter, None);
+        let msg = ExecuteMsg::UpdateGuardian {
+            guardian: guardian.clone(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+
+        // the admin is not the guardian anymore
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&guardian, &[]),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap();
+
+        let transfer = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: Uint128::new(100),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&addr1, &[]),
+            transfer.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Paused);
+        let msg = ExecuteMsg::Send {
+            contract: addr2.clone(),
+            amount: Uint128::new(100),
+            msg: Binary::default(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Paused);
+        let msg = ExecuteMsg::Mint {
+            recipient: addr2.clone(),
+            amount: Uint128::new(100),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(&minter, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Paused);
+
+        // queries still work
+        assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(1000));
+        let state: PauseStateResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::PauseState {}).unwrap()).unwrap();
+        assert_eq!(
+            state,
+            PauseStateResponse {
+                paused: true,
+                guardian: Addr::unchecked(&guardian),
+            }
+        );
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&guardian, &[]),
+            ExecuteMsg::Unpause {},
+        )
+        .unwrap();
+        execute(deps.as_mut(), env, mock_info(&addr1, &[]), transfer).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(100));
+    }
+
+    #[test]
+    fn transfer_fee() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let addr2 = deps.api.addr_make("addr0002").to_string();
+        let spender = deps.api.addr_make("spender").to_string();
+        let collector = deps.api.addr_make("collector").to_string();
+        let reward_contract = deps.api.addr_make("reward-contract").to_string();
+        let env = mock_env();
+
+        let instantiate_msg = InstantiateMsg {
+            initial_balances: vec![Cw20Coin {
+                address: addr1.clone(),
+                amount: Uint128::new(10000),
+            }],
+            transfer_fee: Some(TransferFee {
+                fee_bps: 1001,
+                collector: collector.clone(),
+            }),
+            ..Default::default()
+        };
+        let err = instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            instantiate_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TransferFeeTooHigh { max: 1000 });
+        let instantiate_msg = InstantiateMsg {
+            transfer_fee: Some(TransferFee {
+                fee_bps: 100,
+                collector: collector.clone(),
+            }),
+            ..instantiate_msg
+        };
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            instantiate_msg,
+        )
+        .unwrap();
+
+        // 1% of every transfer goes to the collector
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: Uint128::new(1000),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(9000));
+        assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(990));
+        assert_eq!(get_balance(deps.as_ref(), &collector), Uint128::new(10));
+        assert!(res.attributes.contains(&attr("amount", "990")));
+        assert!(res.attributes.contains(&attr("transfer_fee", "10")));
+        assert!(res
+            .attributes
+            .contains(&attr("fee_recipient", collector.as_str())));
+
+        // the receiver of a send is told the amount it actually got
+        let msg = ExecuteMsg::Send {
+            contract: reward_contract.clone(),
+            amount: Uint128::new(1000),
+            msg: Binary::default(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+        let receive = Cw20ReceiveMsg {
+            sender: addr1.clone(),
+            amount: Uint128::new(990),
+            msg: Binary::default(),
+        }
+        .into_cosmos_msg(reward_contract.clone())
+        .unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(receive)]);
+        assert_eq!(get_balance(deps.as_ref(), &collector), Uint128::new(20));
+
+        // allowance based transfers pay the fee out of the allowance
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(1000),
+            expires: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+        let msg = ExecuteMsg::TransferFrom {
+            owner: addr1.clone(),
+            recipient: addr2.clone(),
+            amount: Uint128::new(1000),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&spender, &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(7000));
+        assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(1980));
+        assert_eq!(get_balance(deps.as_ref(), &collector), Uint128::new(30));
+        assert_eq!(
+            query_allowance(deps.as_ref(), addr1.clone(), spender)
+                .unwrap()
+                .allowance,
+            Uint128::zero()
+        );
+
+        // exempt accounts pay no fee
+        let msg = ExecuteMsg::UpdateFeeExemptions {
+            add: vec![reward_contract.clone()],
+            remove: vec![],
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&addr1, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Transfer {
+            recipient: reward_contract.clone(),
+            amount: Uint128::new(1000),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(
+            get_balance(deps.as_ref(), &reward_contract),
+            Uint128::new(1990)
+        );
+        assert_eq!(get_balance(deps.as_ref(), &collector), Uint128::new(30));
+
+        let fee_config: FeeConfigResponse =
+            from_json(query(deps.as_ref(), env, QueryMsg::FeeConfig {}).unwrap()).unwrap();
+        assert_eq!(
+            fee_config,
+            FeeConfigResponse {
+                fee_bps: 100,
+                collector: Addr::unchecked(collector),
+                exempt: vec![Addr::unchecked(reward_contract)],
+            }
+        );
+    }
+
+    #[test]
+    fn migrate_from_cw20_base_storage() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let addr2 = deps.api.addr_make("addr0002").to_string();
+        let admin = deps.api.addr_make("admin").to_string();
+        let env = mock_env();
+
+        // the storage of the first wtoken version, which left everything to cw20-base
+        let cw20_init_msg = cw20_base::msg::InstantiateMsg {
+            name: TOKEN_NAME.into(),
+            symbol: TOKEN_SYMBOL.into(),
+            decimals: TOKEN_DECIMALS,
+            initial_balances: vec![
+                Cw20Coin {
+                    address: addr1.clone(),
+                    amount: Uint128::new(1000),
+                },
+                Cw20Coin {
+                    address: addr2.clone(),
+                    amount: Uint128::new(500),
+                },
+            ],
+            mint: None,
+            marketing: None,
+        };
+        cw20_base::contract::instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            cw20_init_msg,
+        )
+        .unwrap();
+
+        let msg = MigrateMsg {
+            admin: None,
+            freezable: None,
+        };
+        let err = migrate(deps.as_mut(), env.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::MigrationAdminRequired);
+
+        let msg = MigrateMsg {
+            admin: Some(admin.clone()),
+            freezable: None,
+        };
+        migrate(deps.as_mut(), env.clone(), msg).unwrap();
+
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+        assert_eq!(get_holder_count(deps.as_ref()), 2);
+        let state: PauseStateResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::PauseState {}).unwrap()).unwrap();
+        assert_eq!(
+            state,
+            PauseStateResponse {
+                paused: false,
+                guardian: Addr::unchecked(&admin),
+            }
+        );
+
+        // balances survive and the new features work on top of them
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: Uint128::new(1000),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(1500));
+        assert_eq!(get_holder_count(deps.as_ref()), 1);
+        let msg = ExecuteMsg::Freeze { address: addr2 };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(&admin, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::FreezeDisabled);
+
+        // migrating again keeps the state
+        let msg = MigrateMsg {
+            admin: None,
+            freezable: None,
+        };
+        migrate(deps.as_mut(), env, msg).unwrap();
+        assert_eq!(get_holder_count(deps.as_ref()), 1);
+    }
+
+    #[test]
+    fn migrate_refuses_downgrades_and_other_contracts() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        do_instantiate(deps.as_mut(), &addr1, Uint128::new(1000));
+        let msg = MigrateMsg {
+            admin: None,
+            freezable: None,
+        };
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrate {
+                contract: CONTRACT_NAME.into(),
+                version: "99.0.0".into()
+            }
+        );
+
+        set_contract_version(deps.as_mut().storage, "crates.io:other", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrate {
+                contract: "crates.io:other".into(),
+                version: "0.1.0".into()
+            }
+        );
+    }
+
+    #[test]
+    fn responses_report_balance_changes() {
+        let mut deps = mock_dependencies();
+        let addr1 = deps.api.addr_make("addr0001").to_string();
+        let addr2 = deps.api.addr_make("addr0002").to_string();
+        let minter = deps.api.addr_make("minter").to_string();
+        let env = mock_env();
+
+        let instantiate_msg = InstantiateMsg {
+            initial_balances: vec![Cw20Coin {
+                address: addr1.clone(),
+                amount: Uint128::new(1000),
+            }],
+            mint: Some(MinterResponse {
+                minter: minter.clone(),
+                cap: None,
+            }),
+            ..Default::default()
+        };
+        let res = instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            instantiate_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "instantiate"),
+                attr("name", TOKEN_NAME),
+                attr("symbol", TOKEN_SYMBOL),
+                attr("decimals", "6"),
+                attr("new_supply", "1000"),
+            ]
+        );
+
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: Uint128::new(100),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "transfer"),
+                attr("from", &addr1),
+                attr("to", &addr2),
+                attr("amount", "100"),
+                attr("new_supply", "1000"),
+            ]
+        );
+
+        let msg = ExecuteMsg::Mint {
+            recipient: addr2.clone(),
+            amount: Uint128::new(50),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(&minter, &[]), msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "mint"),
+                attr("from", ""),
+                attr("to", &addr2),
+                attr("amount", "50"),
+                attr("new_supply", "1050"),
+            ]
+        );
+
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(150),
+        };
+        let res = execute(deps.as_mut(), env, mock_info(&addr2, &[]), msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "burn"),
+                attr("from", &addr2),
+                attr("to", ""),
+                attr("amount", "150"),
+                attr("new_supply", "900"),
+            ]
+        );
+    }
 }
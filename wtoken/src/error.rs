@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
 use cw20::Cw20ExecuteMsg;
 pub use cw20_base::ContractError as CW20BaseError;
 use thiserror::Error;
@@ -11,6 +11,60 @@ pub enum ContractError {
     CW20Base(#[from] CW20BaseError),
     #[error("Not supported execution")]
     NotSupportedExecution(Cw20ExecuteMsg),
+    #[error("Unauthorized")]
+    Unauthorized,
+    #[error("Hook {contract} is already registered")]
+    HookAlreadyRegistered { contract: String },
+    #[error("Hook {contract} is not registered")]
+    HookNotRegistered { contract: String },
+    #[error("No more than {max} hooks can be registered")]
+    TooManyHooks { max: usize },
+    #[error("Invalid permit signature")]
+    InvalidPermitSignature,
+    #[error("Permit public key does not belong to the owner")]
+    PermitSignerMismatch,
+    #[error("Invalid permit nonce, expected {expected}")]
+    InvalidPermitNonce { expected: u64 },
+    #[error("Permit expired")]
+    PermitExpired,
+    #[error("Batch is empty")]
+    EmptyBatch,
+    #[error("Batch has more than {max} entries")]
+    BatchTooLarge { max: u32 },
+    #[error("Minter quota exceeded, {remaining} left in the current window")]
+    MinterQuotaExceeded { remaining: Uint128 },
+    #[error("Minter quota window can't be zero")]
+    ZeroMinterQuotaWindow,
+    #[error("Minter {minter} is not registered")]
+    MinterNotRegistered { minter: String },
+    #[error("Invalid vesting schedule: {reason}")]
+    InvalidVestingSchedule { reason: String },
+    #[error("No more than {max} vesting schedules per account")]
+    TooManyVestingSchedules { max: usize },
+    #[error("{locked} tokens of {address} are still locked by vesting")]
+    LockedByVesting { address: String, locked: Uint128 },
+    #[error("Account {address} is frozen")]
+    AccountFrozen { address: String },
+    #[error("Freezing accounts is disabled for this token")]
+    FreezeDisabled,
+    #[error("Token transfers are paused")]
+    Paused,
+    #[error("Transfer fee can't be more than {max} basis points")]
+    TransferFeeTooHigh { max: u16 },
+    #[error("Cannot migrate from {contract} {version}")]
+    CannotMigrate { contract: String, version: String },
+    #[error("An admin is required to migrate from cw20-base storage")]
+    MigrationAdminRequired,
+    #[error("{0}")]
+    SemVer(String),
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
 }
 
 pub type ContractResult<T> = Result<T, ContractError>;
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
+}
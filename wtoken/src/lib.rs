@@ -1,3 +1,4 @@
 pub mod contract;
 pub mod error;
 pub mod msg;
+pub mod state;
@@ -1,6 +1,11 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Binary, Uint128};
-use cw20::{Cw20Coin, MinterResponse};
+use cosmwasm_std::{
+    to_json_binary, to_json_vec, Addr, Binary, CosmosMsg, StdResult, Timestamp, Uint128, WasmMsg,
+};
+use cw20::{Cw20Coin, Expiration, Logo, MinterResponse};
+use cw20_base::msg::InstantiateMarketingInfo;
+
+use crate::state::{HookFailurePolicy, VestingCurve, VestingSchedule};
 
 #[cw_serde]
 #[derive(QueryResponses)]
@@ -11,17 +16,200 @@ pub enum QueryMsg {
     TokenInfo {},
     #[returns(cw20::MinterResponse)]
     Minter {},
+    #[returns(cw20::AllowanceResponse)]
+    Allowance { owner: String, spender: String },
+    #[returns(cw20::AllAllowancesResponse)]
+    AllAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(cw20::AllSpenderAllowancesResponse)]
+    AllSpenderAllowances {
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(cw20::AllAccountsResponse)]
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(HolderCountResponse)]
+    HolderCount {},
+    /// Balance at the start of block `height`
+    #[returns(cw20::BalanceResponse)]
+    BalanceAt { address: String, height: u64 },
+    /// Total supply at the start of block `height`
+    #[returns(TotalSupplyAtResponse)]
+    TotalSupplyAt { height: u64 },
+    #[returns(HooksResponse)]
+    Hooks {},
+    #[returns(MaxBatchSizeResponse)]
+    MaxBatchSize {},
+    #[returns(PauseStateResponse)]
+    PauseState {},
+    #[returns(FeeConfigResponse)]
+    FeeConfig {},
+    #[returns(IsFrozenResponse)]
+    IsFrozen { address: String },
+    #[returns(FrozenAccountsResponse)]
+    FrozenAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// `Balance` includes locked tokens, this splits it up
+    #[returns(VestingInfoResponse)]
+    VestingInfo { address: String },
+    /// Minters with a quota, next to the cw20 minter returned by `Minter {}`
+    #[returns(MintersResponse)]
+    Minters {},
+    #[returns(MinterAllowanceResponse)]
+    MinterAllowance { minter: String },
+    /// Nonce the next permit of `owner` must be signed with
+    #[returns(PermitNonceResponse)]
+    PermitNonce { owner: String },
+    #[returns(cw20::MarketingInfoResponse)]
+    MarketingInfo {},
+    /// Only embedded logos can be downloaded
+    #[returns(cw20::DownloadLogoResponse)]
+    DownloadLogo {},
+}
+
+#[cw_serde]
+pub struct HolderCountResponse {
+    pub count: u64,
+}
+
+#[cw_serde]
+pub struct TotalSupplyAtResponse {
+    pub total_supply: Uint128,
+}
+
+#[cw_serde]
+pub struct MinterInfo {
+    pub minter: Addr,
+    pub quota: Uint128,
+    pub window: u64,
+}
+
+#[cw_serde]
+pub struct MintersResponse {
+    pub minters: Vec<MinterInfo>,
+}
+
+#[cw_serde]
+pub struct MinterAllowanceResponse {
+    /// Left to mint in the current window
+    pub remaining: Uint128,
+    pub window_end: Timestamp,
+}
+
+#[cw_serde]
+pub struct FeeConfigResponse {
+    pub fee_bps: u16,
+    pub collector: Addr,
+    pub exempt: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct PauseStateResponse {
+    pub paused: bool,
+    pub guardian: Addr,
 }
 
+#[cw_serde]
+pub struct IsFrozenResponse {
+    pub frozen: bool,
+}
+
+#[cw_serde]
+pub struct FrozenAccountsResponse {
+    pub accounts: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct VestingInfoResponse {
+    pub locked: Uint128,
+    pub unlocked: Uint128,
+    pub schedules: Vec<VestingSchedule>,
+}
+
+#[cw_serde]
+pub struct MaxBatchSizeResponse {
+    pub max_batch_size: u32,
+}
+
+#[cw_serde]
+pub struct PermitNonceResponse {
+    pub nonce: u64,
+}
+
+#[cw_serde]
+pub struct HookInfo {
+    pub contract: Addr,
+    pub on_failure: HookFailurePolicy,
+}
+
+#[cw_serde]
+pub struct HooksResponse {
+    pub admin: Addr,
+    pub hooks: Vec<HookInfo>,
+}
+
+/// `name`, `symbol` and `decimals` default to "Wtoken", "WTK" and 6.
+/// They are validated the same way cw20-base does.
 #[cw_serde]
 #[cfg_attr(test, derive(Default))]
 pub struct InstantiateMsg {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
     pub initial_balances: Vec<Cw20Coin>,
     pub mint: Option<MinterResponse>,
+    pub marketing: Option<InstantiateMarketingInfo>,
+    /// Manages the hook contracts, defaults to the instantiator
+    pub admin: Option<String>,
+    /// Defaults to 100
+    pub max_batch_size: Option<u32>,
+    /// Added on top of `initial_balances`, locked until vested
+    pub initial_vesting: Option<Vec<VestingCoin>>,
+    /// Can pause the token, defaults to the admin
+    pub guardian: Option<String>,
+    /// Defaults to no fee, collected by the admin
+    pub transfer_fee: Option<TransferFee>,
+    /// Allows the admin to freeze accounts, defaults to true.
+    /// `false` disables freezing for good.
+    pub freezable: Option<bool>,
+}
+
+/// Only read when migrating a token without wtoken state, e.g. one instantiated as plain
+/// cw20-base. The guardian and the fee collector default to `admin`.
+#[cw_serde]
+pub struct MigrateMsg {
+    pub admin: Option<String>,
+    /// Defaults to false, existing holders didn't sign up for freezing
+    pub freezable: Option<bool>,
+}
+
+#[cw_serde]
+pub struct TransferFee {
+    /// At most 1000 (10%)
+    pub fee_bps: u16,
+    pub collector: String,
+}
+
+#[cw_serde]
+pub struct VestingCoin {
+    pub address: String,
+    pub amount: Uint128,
+    pub vesting: VestingCurve,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
+    /// `Transfer`, `Send`, `TransferFrom`, `SendFrom` and `BatchTransfer` pay the transfer
+    /// fee out of the transferred amount, unless the sender or the recipient is exempt
     Transfer {
         recipient: String,
         amount: Uint128,
@@ -41,4 +229,169 @@ pub enum ExecuteMsg {
     UpdateMinter {
         new_minter: Option<String>,
     },
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    DecreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    SendFrom {
+        owner: String,
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    BurnFrom {
+        owner: String,
+        amount: Uint128,
+    },
+    /// Only callable by the marketing address. `Some("")` clears a field
+    UpdateMarketing {
+        project: Option<String>,
+        description: Option<String>,
+        marketing: Option<String>,
+    },
+    /// Only callable by the marketing address. Embedded PNG/SVG logos are limited to 5KB
+    UploadLogo(Logo),
+    /// Only callable by the admin
+    AddHook {
+        contract: String,
+        on_failure: HookFailurePolicy,
+    },
+    /// Only callable by the admin
+    RemoveHook {
+        contract: String,
+    },
+    /// Can be submitted by anyone on behalf of the owner
+    Permit(PermitMsg),
+    /// Transfers from the sender to every recipient, all or nothing
+    BatchTransfer {
+        transfers: Vec<Cw20Coin>,
+    },
+    /// Only callable by the minter, mints to every recipient, all or nothing
+    BatchMint {
+        mints: Vec<Cw20Coin>,
+    },
+    /// Only callable by the admin
+    UpdateMaxBatchSize {
+        max_batch_size: u32,
+    },
+    /// Only callable by the admin. Adds a minter or changes its quota, which allows minting
+    /// up to `quota` every `window` seconds next to the cw20 minter
+    SetMinterQuota {
+        minter: String,
+        quota: Uint128,
+        window: u64,
+    },
+    /// Only callable by the admin
+    RemoveMinter {
+        minter: String,
+    },
+    /// Only callable by the guardian, stops every balance change until unpaused
+    Pause {},
+    /// Only callable by the guardian
+    Unpause {},
+    /// Only callable by the admin
+    UpdateGuardian {
+        guardian: String,
+    },
+    /// Only callable by the admin
+    UpdateTransferFee(TransferFee),
+    /// Only callable by the admin
+    UpdateFeeExemptions {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Only callable by the admin, the account can't send or receive tokens until unfrozen
+    Freeze {
+        address: String,
+    },
+    /// Only callable by the admin
+    Unfreeze {
+        address: String,
+    },
+    /// Same as `Mint`, the minted amount is locked until vested
+    MintVesting {
+        recipient: String,
+        amount: Uint128,
+        vesting: VestingCurve,
+    },
+}
+
+/// Sets the allowance of `spender` over `owner`'s tokens to `amount`, authorized by
+/// the owner's secp256k1 signature over the sha256 hash of the `PermitSignDoc` JSON.
+/// The permit can't be used after `expires`, which is also the expiration of the allowance.
+#[cw_serde]
+pub struct PermitMsg {
+    pub owner: String,
+    pub spender: String,
+    pub amount: Uint128,
+    pub expires: Option<Expiration>,
+    pub nonce: u64,
+    pub signature: Binary,
+    /// Compressed secp256k1 public key of the owner
+    pub pubkey: Binary,
+}
+
+/// The document an owner signs to authorize a `Permit`.
+#[cw_serde]
+pub struct PermitSignDoc {
+    /// Always `PERMIT_DOMAIN`, so the signature can't be reused for another purpose
+    pub domain: String,
+    pub chain_id: String,
+    pub contract: String,
+    pub owner: String,
+    pub spender: String,
+    pub amount: Uint128,
+    pub expires: Option<Expiration>,
+    pub nonce: u64,
+}
+
+pub const PERMIT_DOMAIN: &str = "wtoken_permit";
+
+impl PermitSignDoc {
+    /// The canonical JSON bytes to be hashed and signed
+    pub fn to_sign_bytes(&self) -> StdResult<Vec<u8>> {
+        to_json_vec(self)
+    }
+}
+
+/// Sent to every hook contract for each account whose balance changed.
+#[cw_serde]
+pub struct BalanceChangedHookMsg {
+    pub address: String,
+    pub old: Uint128,
+    pub new: Uint128,
+}
+
+impl BalanceChangedHookMsg {
+    pub fn into_json_binary(self) -> StdResult<Binary> {
+        let msg = HookExecuteMsg::BalanceChangedHook(self);
+        to_json_binary(&msg)
+    }
+
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_json_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+// This is just a helper to properly serialize the above message
+#[cw_serde]
+enum HookExecuteMsg {
+    BalanceChangedHook(BalanceChangedHookMsg),
 }
@@ -0,0 +1,189 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Empty, Timestamp, Uint128};
+use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
+
+/// Manages the hook contracts
+pub const ADMIN: Item<Addr> = Item::new("admin");
+/// Contracts notified with a `BalanceChangedHook` on every balance change
+pub const HOOKS: Map<&Addr, HookFailurePolicy> = Map::new("hooks");
+pub const MAX_HOOKS: usize = 10;
+/// Can pause and unpause all balance changes
+pub const GUARDIAN: Item<Addr> = Item::new("guardian");
+pub const PAUSED: Item<bool> = Item::new("paused");
+pub const FEE_CONFIG: Item<FeeConfig> = Item::new("fee_config");
+/// Transfers from or to these accounts pay no transfer fee
+pub const FEE_EXEMPT: Map<&Addr, Empty> = Map::new("fee_exempt");
+pub const MAX_TRANSFER_FEE_BPS: u16 = 1_000;
+/// Whether the admin can freeze accounts, fixed at instantiation
+pub const FREEZABLE: Item<bool> = Item::new("freezable");
+/// Accounts that can't send or receive tokens
+pub const FROZEN: Map<&Addr, Empty> = Map::new("frozen");
+/// Additional minters managed by the admin, each limited to `quota` per `window`
+pub const MINTERS: Map<&Addr, MinterQuota> = Map::new("minters");
+/// Vesting schedules locking part of an account's balance
+pub const VESTING: Map<&Addr, Vec<VestingSchedule>> = Map::new("vesting");
+pub const MAX_VESTING_SCHEDULES: usize = 20;
+/// Maximum number of legs in a `BatchTransfer` or `BatchMint`
+pub const MAX_BATCH_SIZE: Item<u32> = Item::new("max_batch_size");
+pub const DEFAULT_MAX_BATCH_SIZE: u32 = 100;
+/// Next permit nonce expected from each owner
+pub const PERMIT_NONCES: Map<&Addr, u64> = Map::new("permit_nonces");
+
+/// Number of accounts holding a non-zero Wtoken balance
+pub const HOLDER_COUNT: Item<u64> = Item::new("holder_count");
+/// Copy of the cw20-base balances, checkpointed on every block they change in
+pub const BALANCE_SNAPSHOTS: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "balance_snapshots",
+    "balance_snapshots__checkpoints",
+    "balance_snapshots__changelog",
+    Strategy::EveryBlock,
+);
+pub const TOTAL_SUPPLY_SNAPSHOTS: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_supply_snapshots",
+    "total_supply_snapshots__checkpoints",
+    "total_supply_snapshots__changelog",
+    Strategy::EveryBlock,
+);
+
+#[cw_serde]
+pub struct FeeConfig {
+    /// Taken from every transfer, in basis points
+    pub fee_bps: u16,
+    pub collector: Addr,
+}
+
+/// What happens to the balance change when a hook contract fails
+#[cw_serde]
+pub enum HookFailurePolicy {
+    Revert,
+    Ignore,
+}
+
+#[cw_serde]
+pub struct MinterQuota {
+    /// Amount that can be minted per window
+    pub quota: Uint128,
+    /// Window length in seconds
+    pub window: u64,
+    pub window_start: Timestamp,
+    /// Minted in the current window
+    pub minted: Uint128,
+}
+
+impl MinterQuota {
+    pub fn new(quota: Uint128, window: u64, now: Timestamp) -> Self {
+        Self {
+            quota,
+            window,
+            window_start: now,
+            minted: Uint128::zero(),
+        }
+    }
+
+    /// Starts a new window once the current one is over
+    pub fn settle(&mut self, now: Timestamp) {
+        if now >= self.window_end() {
+            // Windows stay aligned to the first one
+            let elapsed = now.seconds() - self.window_start.seconds();
+            let skipped = elapsed - elapsed % self.window;
+            self.window_start = self.window_start.plus_seconds(skipped);
+            self.minted = Uint128::zero();
+        }
+    }
+
+    pub fn window_end(&self) -> Timestamp {
+        self.window_start.plus_seconds(self.window)
+    }
+
+    pub fn remaining(&self) -> Uint128 {
+        self.quota.saturating_sub(self.minted)
+    }
+}
+
+/// How a vested amount unlocks over time
+#[cw_serde]
+pub enum VestingCurve {
+    /// Nothing unlocks before `cliff`, then the amount unlocks linearly from `start` to `end`
+    Linear {
+        start: Timestamp,
+        cliff: Timestamp,
+        end: Timestamp,
+    },
+    /// Nothing unlocks before the first step. Between two steps the unlocked amount grows
+    /// linearly, the last step must unlock the whole amount
+    Piecewise { steps: Vec<VestingStep> },
+}
+
+#[cw_serde]
+pub struct VestingStep {
+    pub time: Timestamp,
+    /// Total amount unlocked at `time`
+    pub unlocked: Uint128,
+}
+
+#[cw_serde]
+pub struct VestingSchedule {
+    pub amount: Uint128,
+    pub curve: VestingCurve,
+}
+
+impl VestingSchedule {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        match &self.curve {
+            VestingCurve::Linear { start, cliff, end } => {
+                if start > cliff || cliff > end {
+                    return Err("start <= cliff <= end is required");
+                }
+            }
+            VestingCurve::Piecewise { steps } => {
+                let last = steps.last().ok_or("at least one step is required")?;
+                if last.unlocked != self.amount {
+                    return Err("the last step must unlock the whole amount");
+                }
+                if steps
+                    .windows(2)
+                    .any(|pair| pair[0].time >= pair[1].time || pair[0].unlocked > pair[1].unlocked)
+                {
+                    return Err("steps must be sorted by time and unlock more over time");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn locked(&self, now: Timestamp) -> Uint128 {
+        self.amount - self.unlocked(now)
+    }
+
+    fn unlocked(&self, now: Timestamp) -> Uint128 {
+        match &self.curve {
+            VestingCurve::Linear { start, cliff, end } => {
+                if now < *cliff {
+                    Uint128::zero()
+                } else if now >= *end {
+                    self.amount
+                } else {
+                    self.amount.multiply_ratio(
+                        now.seconds() - start.seconds(),
+                        end.seconds() - start.seconds(),
+                    )
+                }
+            }
+            VestingCurve::Piecewise { steps } => {
+                let next = steps.iter().position(|step| step.time > now);
+                match next {
+                    Some(0) => Uint128::zero(),
+                    Some(i) => {
+                        let (prev, next) = (&steps[i - 1], &steps[i]);
+                        prev.unlocked
+                            + (next.unlocked - prev.unlocked).multiply_ratio(
+                                now.seconds() - prev.time.seconds(),
+                                next.time.seconds() - prev.time.seconds(),
+                            )
+                    }
+                    None => self.amount,
+                }
+            }
+        }
+    }
+}